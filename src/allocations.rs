@@ -0,0 +1,111 @@
+#[cfg(feature = "allocations")]
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicBool, AtomicIsize, AtomicUsize, Ordering};
+
+/// Wraps the system allocator to count allocations, but only while [`count_allocations`] runs.
+///
+/// Only installed with the `allocations` feature, since it makes every allocation a little slower,
+/// even while not counting.
+#[cfg(feature = "allocations")]
+pub(crate) struct CountingAllocator;
+
+static COUNTING: AtomicBool = AtomicBool::new(false);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static TOTAL_BYTES: AtomicUsize = AtomicUsize::new(0);
+/// Signed, since memory allocated before counting started might be freed while counting.
+static CURRENT_BYTES: AtomicIsize = AtomicIsize::new(0);
+static PEAK_BYTES: AtomicIsize = AtomicIsize::new(0);
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct AllocationStats {
+    pub(crate) allocations: usize,
+    /// The sum of all allocated bytes, regardless of whether they were freed in the meantime.
+    pub(crate) total_bytes: usize,
+    /// The most bytes allocated at the same time.
+    pub(crate) peak_bytes: usize,
+}
+
+/// Counts the allocations made while running `f`.
+///
+/// The counters are shared by all threads, so nothing else may allocate in the meantime.
+pub(crate) fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, AllocationStats) {
+    ALLOCATIONS.store(0, Ordering::SeqCst);
+    TOTAL_BYTES.store(0, Ordering::SeqCst);
+    CURRENT_BYTES.store(0, Ordering::SeqCst);
+    PEAK_BYTES.store(0, Ordering::SeqCst);
+    COUNTING.store(true, Ordering::SeqCst);
+    let result = f();
+    COUNTING.store(false, Ordering::SeqCst);
+    let stats = AllocationStats {
+        allocations: ALLOCATIONS.load(Ordering::SeqCst),
+        total_bytes: TOTAL_BYTES.load(Ordering::SeqCst),
+        peak_bytes: PEAK_BYTES.load(Ordering::SeqCst).max(0) as usize,
+    };
+    (result, stats)
+}
+
+#[cfg(feature = "allocations")]
+fn record_allocation(size: usize) {
+    if COUNTING.load(Ordering::Relaxed) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        TOTAL_BYTES.fetch_add(size, Ordering::Relaxed);
+        let current = CURRENT_BYTES.fetch_add(size as isize, Ordering::Relaxed) + size as isize;
+        PEAK_BYTES.fetch_max(current, Ordering::Relaxed);
+    }
+}
+
+#[cfg(feature = "allocations")]
+fn record_deallocation(size: usize) {
+    if COUNTING.load(Ordering::Relaxed) {
+        CURRENT_BYTES.fetch_sub(size as isize, Ordering::Relaxed);
+    }
+}
+
+// SAFETY: Everything is forwarded to the system allocator as is.
+#[cfg(feature = "allocations")]
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record_allocation(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record_allocation(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record_deallocation(layout.size());
+    }
+
+    /// Counts as freeing the old and allocating the new memory.
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record_deallocation(layout.size());
+            record_allocation(new_size);
+        }
+        new_ptr
+    }
+}
+
+#[cfg(all(test, feature = "allocations"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocations_are_counted() {
+        // Other tests might allocate at the same time, so only lower bounds are reliable.
+        let (vec, stats) = count_allocations(|| vec![0_u8; 1000]);
+        assert!(stats.allocations >= 1);
+        assert!(stats.total_bytes >= vec.len());
+        assert!(stats.peak_bytes >= vec.len());
+    }
+}
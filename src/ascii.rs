@@ -0,0 +1,73 @@
+use std::{
+    borrow::Cow,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+/// Decides once whether output is limited to ASCII, e.g. drawing tables without box-drawing
+/// characters.
+pub(crate) fn init(ascii: bool) {
+    ASCII.store(ascii, Ordering::Relaxed);
+}
+
+/// Replaces box-drawing characters and other symbols with ASCII ones if `--ascii` is given.
+///
+/// Box-drawing characters are replaced by exactly one other character, so the alignment of table
+/// columns is kept. `±` becomes `+-` and swallows a following space to stay aligned as well.
+pub(crate) fn fallback(text: &str) -> Cow<'_, str> {
+    if !ASCII.load(Ordering::Relaxed) {
+        return Cow::Borrowed(text);
+    }
+    let mut ascii = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '━' => ascii.push('-'),
+            '┃' | '│' => ascii.push('|'),
+            '┏' | '┓' | '┗' | '┛' | '┣' | '┫' | '┳' | '┻' | '╋' | '┯' | '┷' | '┿' => {
+                ascii.push('+')
+            }
+            '±' => {
+                ascii.push_str("+-");
+                chars.next_if_eq(&' ');
+            }
+            '×' => ascii.push('x'),
+            'µ' => ascii.push('u'),
+            char => ascii.push(char),
+        }
+    }
+    Cow::Owned(ascii)
+}
+
+/// Like [`print!`], but limited to ASCII with `--ascii`, see [`fallback`].
+macro_rules! ascii_print {
+    ($($arg:tt)*) => {
+        print!("{}", $crate::ascii::fallback(&format!($($arg)*)))
+    };
+}
+pub(crate) use ascii_print;
+
+/// Like [`println!`], but limited to ASCII with `--ascii`, see [`fallback`].
+macro_rules! ascii_println {
+    ($($arg:tt)*) => {
+        println!("{}", $crate::ascii::fallback(&format!($($arg)*)))
+    };
+}
+pub(crate) use ascii_println;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_fallback_keeps_alignment() {
+        init(true);
+        let border = "┏━━━━━━━━━━━━━━━━━━━━━┓";
+        let row = "┃ 1.05µs ±   1.00ns ┃";
+        assert_eq!(fallback(border), "+---------------------+");
+        assert_eq!(fallback(row), "| 1.05us +-  1.00ns |");
+        assert_eq!(fallback(row).len(), row.chars().count());
+        assert_eq!(fallback("4.62× faster"), "4.62x faster");
+    }
+}
@@ -1,4 +1,7 @@
-use clap::Parser;
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+use serde::Deserialize;
 
 #[derive(Clone, Debug, PartialEq, Parser)]
 #[command(author, version, about, long_about = None)]
@@ -10,26 +13,296 @@ pub(crate) struct Args {
     #[arg(short, long)]
     pub(crate) day: Option<u8>,
 
+    /// Explain how defaults like the year and day were chosen
+    #[arg(short, long)]
+    pub(crate) verbose: bool,
+    /// Print panics of solutions to stderr as usual, including the backtrace if `RUST_BACKTRACE`
+    /// is set
+    #[arg(long)]
+    pub(crate) debug: bool,
+
     /// Run part 2 of the puzzle instead of part 1
     #[arg(short('2'), long)]
     pub(crate) part2: bool,
 
-    /// Which solution to run; defaults to the first solution
-    #[arg(short, long)]
-    pub(crate) solution: Option<String>,
+    /// Solve both parts of every implemented day of the year and show the results as a table
+    #[arg(long, conflicts_with_all = ["day", "part2", "both"])]
+    pub(crate) all: bool,
+    /// Solve both parts of the day one after the other
+    #[arg(long, conflicts_with = "part2")]
+    pub(crate) both: bool,
+
+    /// Which solution to run, by name or as `#N`; defaults to the one marked as default, or else
+    /// the first. Benchmark comparisons and examples also take a comma separated list to only run
+    /// those
+    #[arg(short, long, value_delimiter = ',')]
+    pub(crate) solution: Vec<String>,
+
+    /// List the solutions of both parts, or only part 2 with --part2, and exit
+    #[arg(long)]
+    pub(crate) list_solutions: bool,
 
     /// Run all or a specific example
     #[arg(short, long)]
     pub(crate) example: Option<Option<usize>>,
+    /// Run the examples against every solution
+    #[arg(long)]
+    pub(crate) all_solutions: bool,
+    /// Only show the first N bytes of a failing example's input; 0 shows all of it
+    #[arg(long, value_name = "N", default_value_t = 200)]
+    pub(crate) truncate_input: usize,
+
+    /// How results are compared with expected results and with each other
+    #[arg(long, value_enum, default_value_t)]
+    pub(crate) compare_mode: CompareMode,
 
     /// Benchmark for N seconds; defaults to 1 second if no duration is specified
     #[arg(short, long)]
     pub(crate) bench: Option<Option<f32>>,
+    /// Run each solution for N seconds without measuring before benchmarking it; defaults to 0.1
+    #[arg(long, visible_alias = "warmup", value_name = "SECONDS")]
+    pub(crate) bench_warmup_time: Option<f32>,
     /// Compare benchmark results for alternatives
     #[arg(short, long)]
     pub(crate) compare: bool,
+    /// Also measure CPU cycles per iteration using the timestamp counter (x86_64 only)
+    #[arg(long)]
+    pub(crate) cycles: bool,
+    /// Show benchmark times in this unit instead of picking one for each time
+    #[arg(long, value_enum, default_value_t)]
+    pub(crate) unit: TimeUnit,
+    /// Also count the allocations and allocated bytes of a run; comparisons then benchmark one
+    /// solution at a time. Requires the `allocations` feature
+    #[arg(long)]
+    pub(crate) alloc: bool,
+    /// Don't warn when benchmarking an input that looks like an example
+    #[arg(long)]
+    pub(crate) allow_small_input: bool,
+    /// Discard iterations slower than 10× the median, e.g. on noisy machines
+    #[arg(long)]
+    pub(crate) reject_outliers: bool,
+    /// Ignore the fastest and slowest PERCENT of iterations for the average and standard deviation
+    #[arg(long, value_name = "PERCENT")]
+    pub(crate) trim: Option<f64>,
+    /// Keep benchmarking past the duration until N iterations are collected (up to 60 seconds)
+    #[arg(long, value_name = "N", conflicts_with = "iterations")]
+    pub(crate) bench_min_iterations: Option<usize>,
+    /// Stop benchmarking after N iterations, or at the end of a duration given to --bench if that
+    /// comes first
+    #[arg(long, value_name = "N")]
+    pub(crate) iterations: Option<usize>,
+    /// Benchmark a generated input of about N bytes instead of the real input, if supported
+    #[arg(long, value_name = "N")]
+    pub(crate) synthetic: Option<usize>,
+    /// Benchmark generated inputs of each of the comma separated SIZES to show how the runtime
+    /// scales, if supported
+    #[arg(
+        long,
+        value_name = "SIZES",
+        value_delimiter = ',',
+        num_args = 0..=1,
+        default_missing_value = "1000,10000,100000"
+    )]
+    pub(crate) scaling: Option<Vec<usize>>,
+    /// Test whether the two fastest solutions of a comparison differ significantly
+    #[arg(long)]
+    pub(crate) significance: bool,
+    /// Scale benchmark times using the calibration of this machine
+    #[arg(long)]
+    pub(crate) normalized: bool,
+    /// Benchmark a reference workload to calibrate --normalized for this machine and exit
+    #[arg(long)]
+    pub(crate) calibrate: bool,
+    /// Describe how the benchmark numbers are measured before running it
+    #[arg(long)]
+    pub(crate) explain_benchmark: bool,
+    /// Archive the results of every benchmark run as a JSON file in DIR
+    #[arg(long, value_name = "DIR")]
+    pub(crate) bench_output_dir: Option<PathBuf>,
+
+    /// Solve the input of this session side by side with the one of --session-b
+    #[arg(long, value_name = "SESSION", requires = "session_b")]
+    pub(crate) session_a: Option<String>,
+    /// Solve the input of this session side by side with the one of --session-a
+    #[arg(long, value_name = "SESSION", requires = "session_a")]
+    pub(crate) session_b: Option<String>,
+
+    /// Record the result on the first run and fail if it changes on later runs
+    #[arg(long)]
+    pub(crate) snapshot: bool,
+
+    /// Read the input from PATH, or stdin for `-`, instead of downloading it; needs no session
+    #[arg(short, long, value_name = "PATH")]
+    pub(crate) input: Option<PathBuf>,
+    /// Submit the result as the answer to Advent of Code
+    #[arg(long)]
+    pub(crate) submit: bool,
+    /// Count down until the puzzle unlocks instead of failing when it is not unlocked yet
+    #[arg(long, conflicts_with_all = ["all", "generate", "input"])]
+    pub(crate) wait: bool,
+    /// Fail if the result does not match the answer recorded in `answers.json` by --submit
+    #[arg(long)]
+    pub(crate) verify: bool,
+    /// Fail if the result does not match VALUE, e.g. a confirmed answer in CI
+    #[arg(long, value_name = "VALUE")]
+    pub(crate) assert_answer: Option<String>,
+
+    /// Run every solution, or those given with --solution, on the input and fail unless they all
+    /// agree
+    #[arg(long)]
+    pub(crate) check: bool,
+    /// Check that all solutions agree on generated inputs of up to N bytes, if supported
+    #[arg(long, value_name = "N")]
+    pub(crate) fuzz: Option<usize>,
 
     /// Generate a template for the puzzle
     #[arg(short, long)]
     pub(crate) generate: bool,
+    /// Open the puzzle page in the browser and exit, or after generating the template with
+    /// --generate
+    #[arg(long)]
+    pub(crate) open: bool,
+    /// Overwrite the solution file of an existing day with a fresh template
+    #[arg(long, requires = "generate")]
+    pub(crate) force: bool,
+
+    /// How to output the puzzle result
+    #[arg(short, long, value_enum, default_value_t)]
+    pub(crate) format: OutputFormat,
+    /// Only print the result; short for `--format plain`
+    #[arg(short, long, conflicts_with = "format")]
+    pub(crate) quiet: bool,
+    /// When to color the output; `auto` colors it on a terminal unless `NO_COLOR` is set
+    #[arg(long, value_enum, default_value_t)]
+    pub(crate) color: ColorChoice,
+    /// Draw tables and symbols like ± and µ with ASCII characters, e.g. for consoles and logs
+    /// lacking them
+    #[arg(long)]
+    pub(crate) ascii: bool,
+    /// Group the digits of integer results in the text output, e.g. 1,234,567
+    #[arg(long)]
+    pub(crate) group_digits: bool,
+    /// Also copy the puzzle result to the clipboard
+    #[cfg(feature = "clipboard")]
+    #[arg(long)]
+    pub(crate) clipboard: bool,
+
+    /// List the code blocks and tables of the puzzle page with their index for examples and exit
+    #[arg(long)]
+    pub(crate) list_blocks: bool,
+    /// Print the answers Advent of Code accepted for both parts of the day and exit
+    #[arg(long)]
+    pub(crate) show_answers: bool,
+
+    /// Save the raw puzzle page to PATH, or print it for `-`
+    #[arg(long, value_name = "PATH")]
+    pub(crate) dump_html: Option<PathBuf>,
+
+    /// Read the session from the file at PATH instead of the env var or keyring
+    #[arg(long, value_name = "PATH")]
+    pub(crate) session_file: Option<PathBuf>,
+    /// How to download inputs and puzzle pages
+    #[arg(long, value_enum, default_value_t)]
+    pub(crate) fetch_backend: FetchBackend,
+    /// Download the input and examples again instead of reading them from the cache
+    #[arg(long)]
+    pub(crate) no_cache: bool,
+    /// Don't make any requests and use empty responses instead; results are meaningless
+    #[arg(long)]
+    pub(crate) dry_network: bool,
+    /// Minimum number of milliseconds between two requests to Advent of Code, even across runs;
+    /// defaults to 3 seconds
+    #[arg(long, value_name = "MS")]
+    pub(crate) rate_limit: Option<u64>,
+    /// Abort requests to Advent of Code that take longer than SECONDS; defaults to 30 seconds
+    #[arg(long, value_name = "SECONDS")]
+    pub(crate) timeout: Option<f64>,
+
+    /// Print the file and line each solution of the puzzle is declared at and exit
+    #[arg(long("where"))]
+    pub(crate) locate: bool,
+
+    /// List which parts of the implemented days are still `todo!()` and exit
+    #[arg(long)]
+    pub(crate) todos: bool,
+
+    /// Check the session, network access and config directory and exit
+    #[arg(long)]
+    pub(crate) check_env: bool,
+
+    /// Interactively set up and check the session
+    #[arg(long)]
+    pub(crate) setup: bool,
+
+    /// Print the resolved configuration and exit
+    #[arg(long)]
+    pub(crate) print_config: bool,
+
+    /// Sample a profile for N seconds and list the hottest locations; defaults to 1 second
+    #[cfg(feature = "profile")]
+    #[arg(long, value_name = "SECONDS")]
+    pub(crate) sample: Option<Option<f32>>,
+
+    /// Prompt for the session and store it in the system keyring
+    #[cfg(feature = "keyring")]
+    #[arg(long)]
+    pub(crate) store_session: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum OutputFormat {
+    /// Human readable output
+    #[default]
+    Text,
+    /// One JSON object per line, flushed as soon as each result is available
+    Jsonl,
+    /// Just `year day part solution result` separated by spaces, e.g. for shell loops
+    Line,
+    /// Comma separated benchmark results with a header row, e.g. for spreadsheets
+    Csv,
+    /// Nothing but the result
+    Plain,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ColorChoice {
+    /// Color the output if it is a terminal and `NO_COLOR` is not set
+    #[default]
+    Auto,
+    /// Always color the output, even if `NO_COLOR` is set
+    Always,
+    /// Never color the output
+    Never,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum CompareMode {
+    /// The displayed result has to match exactly
+    #[default]
+    Exact,
+    /// Compare values, ignoring whitespace as well as leading zeros and signs of integers
+    Value,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum TimeUnit {
+    /// Pick a suitable unit for each time
+    #[default]
+    Auto,
+    /// Nanoseconds
+    Ns,
+    /// Microseconds
+    Us,
+    /// Milliseconds
+    Ms,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum FetchBackend {
+    /// Built-in HTTP client
+    #[default]
+    Reqwest,
+    /// Shell out to the system `curl`, e.g. for unusual TLS setups
+    Curl,
 }
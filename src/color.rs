@@ -0,0 +1,73 @@
+use std::{
+    fmt::Display,
+    io::{stdout, IsTerminal},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::cmd::ColorChoice;
+
+/// Disables color when set to anything but an empty string, see <https://no-color.org>.
+const NO_COLOR: &str = "NO_COLOR";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Decides once whether escape sequences are printed; `--color always` wins over `NO_COLOR`.
+pub(crate) fn init(choice: ColorChoice) {
+    let enabled = match choice {
+        ColorChoice::Auto => {
+            let no_color = std::env::var_os(NO_COLOR).is_some_and(|value| !value.is_empty());
+            !no_color && stdout().is_terminal()
+        }
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+    };
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Color {
+    Red,
+    Green,
+    Yellow,
+    Grey,
+}
+
+impl Color {
+    /// The escape sequence that starts the color, for text spanning several prints.
+    pub(crate) fn code(self) -> &'static str {
+        if !enabled() {
+            return "";
+        }
+        match self {
+            Self::Red => "\x1b[31m",
+            Self::Green => "\x1b[32m",
+            Self::Yellow => "\x1b[33m",
+            Self::Grey => "\x1b[90m",
+        }
+    }
+
+    pub(crate) fn paint(self, text: impl Display) -> String {
+        format!("{}{text}{}", self.code(), reset())
+    }
+}
+
+pub(crate) fn reset() -> &'static str {
+    if enabled() {
+        "\x1b[0m"
+    } else {
+        ""
+    }
+}
+
+/// Erases the current line after a `\r`, so progress output can be overwritten.
+pub(crate) fn clear_line() -> &'static str {
+    if enabled() {
+        "\x1b[2K"
+    } else {
+        ""
+    }
+}
@@ -0,0 +1,169 @@
+use std::{
+    collections::BTreeMap,
+    fs::read_to_string,
+    io::ErrorKind,
+    iter::once,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use clap::{parser::ValueSource, ArgMatches};
+use serde::Deserialize;
+
+use crate::{
+    cmd::{Args, OutputFormat},
+    puzzle::{init_aliases, ADVENT_OF_CODE_CACHE_DIR},
+    session::{ADVENT_OF_CODE_SESSION, ADVENT_OF_CODE_SESSION_FILE},
+};
+
+pub(crate) const CONFIG_FILE_NAME: &str = "advent-of-code.toml";
+
+/// Defaults for some of the command line arguments, read from [`CONFIG_FILE_NAME`].
+///
+/// Paths are relative to the directory of the config file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub(crate) struct Config {
+    /// Shorter names for solutions, accepted wherever a solution name is.
+    aliases: BTreeMap<String, String>,
+    /// Used by `--bench` when no duration is given.
+    bench: Option<f32>,
+    /// Used unless `ADVENT_OF_CODE_CACHE_DIR` is set.
+    cache_dir: Option<PathBuf>,
+    /// Used unless `--format` or `--quiet` is given and only in modes accepting it.
+    format: Option<OutputFormat>,
+    /// Used unless `--session-file` is given or the session is set in the environment.
+    session_file: Option<PathBuf>,
+}
+
+/// Reads the config from the working directory, or else the config directory, along with its
+/// path; a missing file is not an error.
+pub(crate) fn load_config() -> Result<Option<(Config, PathBuf)>> {
+    let local = PathBuf::from(CONFIG_FILE_NAME);
+    let global = dirs::config_dir().map(|dir| dir.join("advent-of-code-rs").join(CONFIG_FILE_NAME));
+    for path in once(local).chain(global) {
+        match read_to_string(&path) {
+            Ok(text) => {
+                let config = toml::from_str(&text)
+                    .with_context(|| format!("failed to parse {}", path.display()))?;
+                return Ok(Some((config, path)));
+            }
+            Err(error) if error.kind() == ErrorKind::NotFound => {}
+            Err(error) => {
+                return Err(error).with_context(|| format!("failed to read {}", path.display()))
+            }
+        }
+    }
+    Ok(None)
+}
+
+impl Config {
+    /// Fills in everything that was not given on the command line.
+    ///
+    /// The cache dir and session file go through the environment, so env vars, e.g. from `.env`,
+    /// still win over the config.
+    pub(crate) fn apply(self, path: &Path, args: &mut Args, matches: &ArgMatches) {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        init_aliases(self.aliases);
+        if args.bench == Some(None) {
+            args.bench = Some(self.bench);
+        }
+        if let Some(cache_dir) = self.cache_dir {
+            if std::env::var_os(ADVENT_OF_CODE_CACHE_DIR).is_none() {
+                std::env::set_var(ADVENT_OF_CODE_CACHE_DIR, dir.join(cache_dir));
+            }
+        }
+        if let Some(format) = self.format {
+            if matches.value_source("format") == Some(ValueSource::DefaultValue)
+                && accepts_format(args, format)
+            {
+                args.format = format;
+            }
+        }
+        if let Some(session_file) = self.session_file {
+            if std::env::var_os(ADVENT_OF_CODE_SESSION_FILE).is_none()
+                && std::env::var_os(ADVENT_OF_CODE_SESSION).is_none()
+            {
+                std::env::set_var(ADVENT_OF_CODE_SESSION_FILE, dir.join(session_file));
+            }
+        }
+    }
+}
+
+/// Whether the mode selected by `args` can use `format`, mirroring the checks in `main`, so a
+/// configured format falls back to text instead of being rejected.
+fn accepts_format(args: &Args, format: OutputFormat) -> bool {
+    let text_only = args.generate
+        || args.example.is_some()
+        || args.explain_benchmark
+        || args.bench_output_dir.is_some()
+        || args.scaling.is_some()
+        || args.both
+        || args.submit
+        || args.check
+        || args.alloc;
+    match format {
+        OutputFormat::Text => true,
+        _ if text_only => false,
+        OutputFormat::Jsonl => true,
+        OutputFormat::Line | OutputFormat::Plain => args.bench.is_none(),
+        OutputFormat::Csv => args.bench.is_some() && !args.all,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, FromArgMatches};
+
+    use super::*;
+
+    fn format_for(format: OutputFormat, args: &[&str]) -> OutputFormat {
+        let matches =
+            Args::command().get_matches_from(once("advent-of-code-rs").chain(args.iter().copied()));
+        let mut args = Args::from_arg_matches(&matches).unwrap();
+        let config = Config {
+            format: Some(format),
+            ..Config::default()
+        };
+        config.apply(Path::new(CONFIG_FILE_NAME), &mut args, &matches);
+        args.format
+    }
+
+    #[test]
+    fn configured_format_only_applies_where_accepted() {
+        assert_eq!(format_for(OutputFormat::Csv, &[]), OutputFormat::Text);
+        assert_eq!(
+            format_for(OutputFormat::Csv, &["--bench"]),
+            OutputFormat::Csv
+        );
+        assert_eq!(format_for(OutputFormat::Plain, &[]), OutputFormat::Plain);
+        assert_eq!(
+            format_for(OutputFormat::Plain, &["--bench"]),
+            OutputFormat::Text
+        );
+        assert_eq!(
+            format_for(OutputFormat::Line, &["--bench"]),
+            OutputFormat::Text
+        );
+        assert_eq!(
+            format_for(OutputFormat::Jsonl, &["--bench"]),
+            OutputFormat::Jsonl
+        );
+        assert_eq!(
+            format_for(OutputFormat::Jsonl, &["--generate"]),
+            OutputFormat::Text
+        );
+        assert_eq!(
+            format_for(OutputFormat::Jsonl, &["--example", "0"]),
+            OutputFormat::Text
+        );
+    }
+
+    #[test]
+    fn format_argument_wins_over_config() {
+        assert_eq!(
+            format_for(OutputFormat::Csv, &["--format", "line"]),
+            OutputFormat::Line
+        );
+    }
+}
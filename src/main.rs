@@ -1,26 +1,361 @@
 mod year_2015;
 
+mod allocations;
+mod ascii;
 mod cmd;
+mod color;
+mod config;
 mod puzzle;
+mod session;
+mod stats;
 mod template;
 
-use std::{env::VarError, iter::once, time::Duration};
+use std::{
+    fs::{create_dir_all, remove_file, write},
+    path::PathBuf,
+    slice::from_ref,
+    time::Duration,
+};
 
-use anyhow::{bail, Context, Result};
-use clap::Parser;
-use cmd::Args;
-use puzzle::Puzzle;
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{CommandFactory, FromArgMatches};
+use cmd::{Args, CompareMode, OutputFormat, TimeUnit};
+use color::Color;
+use config::load_config;
+use puzzle::{
+    advent_of_code_now, cache_dir, calibrate, calibration_path, load_calibration_factor,
+    print_todos, set_panic_hook, BenchmarkSettings, Puzzle, PuzzlePart, ADVENT_OF_CODE_URL,
+    CYCLE_COUNTER_AVAILABLE,
+};
+use session::{
+    get_cookie_name, get_session, get_session_with_source, mask_secret, resolve_rate_limit,
+    resolve_timeout, Session,
+};
 use template::generate_template;
 
-const ADVENT_OF_CODE_SESSION: &str = "ADVENT_OF_CODE_SESSION";
+#[cfg(feature = "allocations")]
+#[global_allocator]
+static ALLOCATOR: allocations::CountingAllocator = allocations::CountingAllocator;
 
 fn main() -> Result<()> {
-    dotenv()?;
+    let dotenv_path = dotenv()?;
+    let config = load_config()?;
+
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|error| error.exit());
+    let config_path = config.map(|(config, path)| {
+        config.apply(&path, &mut args, &matches);
+        path
+    });
+    if args.quiet {
+        args.format = OutputFormat::Plain;
+    }
+    color::init(args.color);
+    ascii::init(args.ascii);
+    set_panic_hook(args.debug);
+
+    #[cfg(feature = "keyring")]
+    if args.store_session {
+        return session::store_session();
+    }
+
+    if args.setup {
+        return session::setup(&args, dotenv_path.as_deref());
+    }
+
+    if args.print_config {
+        print_config(&args, dotenv_path, config_path);
+        return Ok(());
+    }
+
+    if args.todos {
+        return print_todos();
+    }
+
+    if args.check_env {
+        return check_env(&args);
+    }
+
+    if args.calibrate {
+        return calibrate();
+    }
+
+    if args.all {
+        if args.generate
+            || args.bench.is_some()
+            || args.example.is_some()
+            || args.snapshot
+            || args.submit
+            || !args.solution.is_empty()
+            || args.format == OutputFormat::Csv
+        {
+            bail!("all days can only be solved without any other mode");
+        }
+        let year = args.year.context("Please specify which year to run")?;
+        return Puzzle::solve_all_for_year(
+            year,
+            &get_session(&args)?,
+            args.format,
+            args.group_digits,
+        );
+    }
 
-    let args = Args::parse();
     let puzzle = Puzzle::from_args(&args)?;
 
-    puzzle.print_header();
+    if args.locate {
+        return puzzle.print_locations();
+    }
+
+    if args.list_solutions {
+        return puzzle.list_solutions(!args.part2);
+    }
+
+    if let Some(path) = &args.dump_html {
+        return puzzle.dump_html(&get_session(&args)?, path);
+    }
+
+    if args.open && !args.generate {
+        puzzle.open_puzzle_page();
+        return Ok(());
+    }
+
+    if args.show_answers {
+        return puzzle.show_answers(&get_session(&args)?);
+    }
+
+    if args.list_blocks {
+        return puzzle.list_blocks(&get_session(&args)?);
+    }
+
+    if args.format == OutputFormat::Text && !args.both {
+        puzzle.print_header();
+    }
+
+    if args.dry_network && args.format == OutputFormat::Text {
+        println!(
+            "{}",
+            Color::Yellow.paint("WARNING: Network is disabled; all inputs and pages are empty")
+        );
+        println!();
+    }
+
+    if args.format != OutputFormat::Text && (args.generate || args.example.is_some()) {
+        bail!("output format can only be used when solving or benchmarking");
+    }
+    if matches!(args.format, OutputFormat::Line | OutputFormat::Plain) && args.bench.is_some() {
+        bail!("line and plain output formats can only be used when solving");
+    }
+    if args.format == OutputFormat::Csv && args.bench.is_none() {
+        bail!("csv output format can only be used with benchmarking");
+    }
+    if args.format != OutputFormat::Text
+        && (args.explain_benchmark || args.bench_output_dir.is_some() || args.scaling.is_some())
+    {
+        bail!("explaining, archiving and scaling benchmarks do not support output formats");
+    }
+    if args.snapshot && (args.generate || args.bench.is_some() || args.example.is_some()) {
+        bail!("snapshots can only be used when solving");
+    }
+    if args.group_digits
+        && (args.generate || args.bench.is_some() || args.example.is_some() || args.snapshot)
+    {
+        bail!("group digits can only be used when solving");
+    }
+    if args.session_a.is_some()
+        && (args.generate || args.bench.is_some() || args.example.is_some() || args.snapshot)
+    {
+        bail!("multiple sessions can only be used when solving");
+    }
+    #[cfg(feature = "clipboard")]
+    if args.clipboard
+        && (args.generate
+            || args.bench.is_some()
+            || args.example.is_some()
+            || args.snapshot
+            || args.session_a.is_some()
+            || args.both)
+    {
+        bail!("clipboard can only be used when solving");
+    }
+
+    if args.both
+        && (args.generate
+            || args.bench.is_some()
+            || args.example.is_some()
+            || args.snapshot
+            || args.session_a.is_some()
+            || args.submit
+            || args.assert_answer.is_some())
+    {
+        bail!("both parts can only be solved without any other mode");
+    }
+    if args.both && (!args.solution.is_empty() || args.format != OutputFormat::Text) {
+        bail!("both parts always use their default solution and the text format");
+    }
+    if args.input.is_some()
+        && (args.generate
+            || args.example.is_some()
+            || args.synthetic.is_some()
+            || args.scaling.is_some()
+            || args.session_a.is_some()
+            || args.submit)
+    {
+        bail!("input can only be used when solving or benchmarking the puzzle input");
+    }
+    if args.submit
+        && (args.generate
+            || args.bench.is_some()
+            || args.example.is_some()
+            || args.snapshot
+            || args.session_a.is_some())
+    {
+        bail!("submitting can only be used when solving");
+    }
+    if args.submit && args.format != OutputFormat::Text {
+        bail!("submitting does not support output formats");
+    }
+    if args.verify
+        && (args.generate
+            || args.bench.is_some()
+            || args.example.is_some()
+            || args.snapshot
+            || args.session_a.is_some()
+            || args.both)
+    {
+        bail!("verifying can only be used when solving");
+    }
+    if args.assert_answer.is_some()
+        && (args.generate
+            || args.bench.is_some()
+            || args.example.is_some()
+            || args.snapshot
+            || args.session_a.is_some())
+    {
+        bail!("asserting an answer can only be used when solving");
+    }
+
+    if args.compare_mode != CompareMode::Exact
+        && args.example.is_none()
+        && args.bench.is_none()
+        && args.assert_answer.is_none()
+        && !args.verify
+        && !args.check
+    {
+        bail!("compare mode can only be used with examples, benchmarks, answer checks or --check");
+    }
+    if args.all_solutions && args.example.is_none() {
+        bail!("all solutions can only be used with examples");
+    }
+    if args.cycles && args.bench.is_none() {
+        bail!("cycles can only be used with benchmarking");
+    }
+    if args.allow_small_input && args.bench.is_none() {
+        bail!("allow small input can only be used with benchmarking");
+    }
+    if args.reject_outliers && args.bench.is_none() {
+        bail!("outlier rejection can only be used with benchmarking");
+    }
+    if args.trim.is_some() && args.bench.is_none() {
+        bail!("trim can only be used with benchmarking");
+    }
+    if args.trim.is_some_and(|trim| !(0.0..50.0).contains(&trim)) {
+        bail!("trim must be at least 0 and less than 50 percent");
+    }
+    if args.bench_min_iterations.is_some() && args.bench.is_none() {
+        bail!("bench min iterations can only be used with benchmarking");
+    }
+    if args.iterations.is_some() && args.bench.is_none() {
+        bail!("iterations can only be used with benchmarking");
+    }
+    if args.iterations == Some(0) {
+        bail!("iterations must be at least 1");
+    }
+    if args.synthetic.is_some() && args.bench.is_none() {
+        bail!("synthetic inputs can only be used with benchmarking");
+    }
+    if args.scaling.is_some() && args.bench.is_none() {
+        bail!("scaling can only be used with benchmarking");
+    }
+    if args.scaling.is_some() && (args.compare || args.synthetic.is_some()) {
+        bail!("scaling cannot be combined with comparisons or a single synthetic input");
+    }
+    if args.significance && !args.compare {
+        bail!("significance can only be used with benchmark comparisons");
+    }
+    if args.bench_warmup_time.is_some() && args.bench.is_none() {
+        bail!("bench warmup time can only be used with benchmarking");
+    }
+    if args.normalized && args.bench.is_none() {
+        bail!("normalization can only be used with benchmarking");
+    }
+    if args.explain_benchmark && args.bench.is_none() {
+        bail!("explain benchmark can only be used with benchmarking");
+    }
+    if args.bench_output_dir.is_some() && args.bench.is_none() {
+        bail!("bench output dir can only be used with benchmarking");
+    }
+    if args.unit != TimeUnit::Auto && args.bench.is_none() {
+        bail!("unit can only be used with benchmarking");
+    }
+    if args.alloc && !cfg!(feature = "allocations") {
+        bail!("allocation counting requires the `allocations` feature");
+    }
+    if args.alloc && args.bench.is_none() {
+        bail!("allocation counting can only be used with benchmarking");
+    }
+    if args.alloc && (args.format != OutputFormat::Text || args.scaling.is_some()) {
+        bail!("allocation counting does not support output formats or scaling");
+    }
+
+    // Only modes fetching the input or the puzzle page need the puzzle to be released.
+    let offline = args.generate
+        || args.fuzz.is_some()
+        || args.input.is_some()
+        || args.synthetic.is_some()
+        || args.scaling.is_some();
+    if !offline {
+        puzzle.wait_for_release(args.wait)?;
+    }
+
+    #[cfg(feature = "profile")]
+    if let Some(sample_duration) = args.sample {
+        if args.generate || args.example.is_some() || args.bench.is_some() {
+            bail!("sampling cannot be combined with templates, examples or benchmarks");
+        }
+
+        return puzzle.print_profile(
+            single_solution(&args)?,
+            &get_session(&args)?,
+            resolve_bench_duration(sample_duration)?,
+        );
+    }
+
+    if let Some(max_size) = args.fuzz {
+        if args.generate || args.example.is_some() || args.bench.is_some() || args.snapshot {
+            bail!("fuzzing cannot be combined with templates, examples, benchmarks or snapshots");
+        }
+        if !args.solution.is_empty() {
+            bail!("fuzzing always runs all solutions");
+        }
+
+        return puzzle.fuzz(max_size);
+    }
+
+    if args.check {
+        if args.generate
+            || args.example.is_some()
+            || args.bench.is_some()
+            || args.snapshot
+            || args.submit
+        {
+            bail!("checking cannot be combined with templates, examples, benchmarks, snapshots or submitting");
+        }
+        if args.format != OutputFormat::Text {
+            bail!("checking does not support output formats");
+        }
+
+        return puzzle.check_solutions(&args.solution, &get_session(&args)?, args.compare_mode);
+    }
 
     if args.generate {
         if args.example.is_some() {
@@ -35,33 +370,83 @@ fn main() -> Result<()> {
         if args.part2 {
             bail!("template generation always generates both parts");
         }
-        if args.solution.is_some() {
+        if !args.solution.is_empty() {
             bail!("template generation does not support generating named solutions");
         }
 
-        generate_template(puzzle.year, puzzle.day)?;
+        generate_template(puzzle.year, puzzle.day, args.force)?;
+        if args.open {
+            println!();
+            puzzle.open_puzzle_page();
+        }
     } else if let Some(bench_duration) = args.bench {
         if args.example.is_some() {
             bail!("benchmark cannot be run on examples");
         }
 
         #[cfg(debug_assertions)]
+        if args.format == OutputFormat::Text {
+            println!(
+                "{}",
+                Color::Yellow.paint("WARNING: Running benchmark with a debug build")
+            );
+            println!();
+        }
+
+        let mut settings = BenchmarkSettings {
+            warmup: resolve_bench_warmup(args.bench_warmup_time)?,
+            // Without an explicit duration, only the number of iterations limits the benchmark.
+            duration: match (bench_duration, args.iterations) {
+                (None, Some(_)) => Duration::MAX,
+                _ => resolve_bench_duration(bench_duration)?,
+            },
+            cycles: args.cycles,
+            allow_small_input: args.allow_small_input,
+            reject_outliers: args.reject_outliers,
+            min_iterations: args.bench_min_iterations,
+            iterations: args.iterations,
+            trim: args.trim,
+            compare_mode: args.compare_mode,
+            synthetic: args.synthetic,
+            significance: args.significance,
+            normalization: args.normalized.then(load_calibration_factor).transpose()?,
+            output_dir: args.bench_output_dir.clone(),
+            unit: args.unit,
+            allocations: args.alloc,
+        };
+        if settings.cycles && !CYCLE_COUNTER_AVAILABLE {
+            if args.format == OutputFormat::Text {
+                println!(
+                    "{}",
+                    Color::Yellow
+                        .paint("WARNING: Cycle counting requires x86_64; measuring time only")
+                );
+                println!();
+            }
+            settings.cycles = false;
+        }
+
+        if args.explain_benchmark {
+            settings.print_explanation(args.compare);
+        }
+        if let Some(factor) = settings
+            .normalization
+            .filter(|_| args.format == OutputFormat::Text)
         {
-            println!("\x1b[33mWARNING: Running benchmark with a debug build\x1b[0m");
+            println!("Normalizing times with the calibration factor {factor:.3}");
             println!();
         }
 
-        let session = &get_session()?;
-        let bench_duration = Duration::from_secs_f32(bench_duration.unwrap_or(1.0));
+        if let Some(sizes) = &args.scaling {
+            return puzzle.print_scaling(single_solution(&args)?, &settings, sizes);
+        }
 
-        if args.compare {
-            if args.solution.is_some() {
-                bail!("compare always runs all solutions");
-            }
+        let session = &get_session(&args)?;
 
-            puzzle.print_benchmark_comparison(session, bench_duration)?;
+        if args.compare {
+            puzzle.print_benchmark_comparison(&args.solution, session, &settings, args.format)?;
         } else {
-            puzzle.print_benchmark(args.solution.as_deref(), session, bench_duration)?;
+            puzzle.print_benchmark(single_solution(&args)?, session, &settings, args.format)?;
         }
     } else if let Some(example) = args.example {
         if args.compare {
@@ -72,48 +457,256 @@ fn main() -> Result<()> {
         if examples.is_empty() {
             bail!("puzzle has no examples");
         }
-        if let Some(example) = example {
-            puzzle.run_examples(
-                args.solution.as_deref(),
-                &get_session()?,
-                once(
-                    *examples.get(example).with_context(|| {
-                        format!("puzzle only has {} example(s)", examples.len())
-                    })?,
-                ),
+        let examples = if let Some(example) = example {
+            from_ref(
+                examples
+                    .get(example)
+                    .with_context(|| format!("puzzle only has {} example(s)", examples.len()))?,
+            )
+        } else {
+            examples
+        };
+
+        if args.all_solutions || args.solution.len() > 1 {
+            if args.all_solutions && !args.solution.is_empty() {
+                bail!("all solutions cannot be combined with specific solutions");
+            }
+
+            puzzle.run_examples_for_all_solutions(
+                &args.solution,
+                &get_session(&args)?,
+                examples.iter().copied(),
+                args.compare_mode,
             )?;
         } else {
             puzzle.run_examples(
-                args.solution.as_deref(),
-                &get_session()?,
+                single_solution(&args)?,
+                &get_session(&args)?,
                 examples.iter().copied(),
+                args.truncate_input,
+                args.compare_mode,
             )?;
-        };
+        }
+    } else if let (Some(session_a), Some(session_b)) = (&args.session_a, &args.session_b) {
+        if args.compare {
+            bail!("compare can only be used with benchmarking");
+        }
+
+        if args.format != OutputFormat::Text {
+            bail!("multiple sessions do not support output formats");
+        }
+
+        puzzle.solve_for_sessions(
+            single_solution(&args)?,
+            &Session::with_cookie(session_a.clone(), &args),
+            session_b,
+            args.group_digits,
+        )?;
+    } else if args.both {
+        if args.compare {
+            bail!("compare can only be used with benchmarking");
+        }
+
+        puzzle.solve_both(&get_session(&args)?, args.group_digits)?;
+    } else if args.snapshot {
+        if args.compare {
+            bail!("compare can only be used with benchmarking");
+        }
+        if args.format != OutputFormat::Text {
+            bail!("snapshots do not support output formats");
+        }
+
+        puzzle.check_snapshot(single_solution(&args)?, &get_session(&args)?)?;
     } else {
         if args.compare {
             bail!("compare can only be used with benchmarking");
         }
 
-        puzzle.solve(args.solution.as_deref(), &get_session()?)?;
+        let session = get_session(&args)?;
+        let result = puzzle.solve(
+            single_solution(&args)?,
+            &session,
+            args.format,
+            args.group_digits,
+        )?;
+
+        #[cfg(feature = "clipboard")]
+        if args.clipboard {
+            copy_to_clipboard(&result.to_string(), args.format);
+        }
+
+        if let Some(expected) = &args.assert_answer {
+            if !result.matches(expected, args.compare_mode) {
+                bail!("result `{result}` does not match asserted answer `{expected}`");
+            }
+        }
+
+        if args.verify {
+            puzzle.verify(&result, args.compare_mode, args.format)?;
+        }
+
+        if args.submit {
+            puzzle.submit(&session, &result)?;
+        }
     }
 
     Ok(())
 }
 
-fn dotenv() -> Result<()> {
+/// The solution selected with `--solution`, for modes that only run a single one.
+fn single_solution(args: &Args) -> Result<Option<&str>> {
+    match args.solution.as_slice() {
+        [] => Ok(None),
+        [solution] => Ok(Some(solution)),
+        _ => bail!(
+            "multiple solutions can only be used with benchmark comparisons, examples and --check"
+        ),
+    }
+}
+
+/// Copies the text to the clipboard, only warning on failure, e.g. in headless environments.
+///
+/// The warning goes to stderr for machine readable formats to keep stdout clean.
+#[cfg(feature = "clipboard")]
+fn copy_to_clipboard(text: &str, output_format: OutputFormat) {
+    if let Err(error) = arboard::Clipboard::new().and_then(|mut clipboard| clipboard.set_text(text))
+    {
+        let warning = Color::Yellow.paint(format!(
+            "WARNING: Failed to copy the result to the clipboard: {error}"
+        ));
+        if output_format == OutputFormat::Text {
+            println!();
+            println!("{warning}");
+        } else {
+            eprintln!("{warning}");
+        }
+    }
+}
+
+fn dotenv() -> Result<Option<PathBuf>> {
     match dotenvy::dotenv() {
-        Ok(_) => Ok(()),
-        Err(err) if err.not_found() => Ok(()),
+        Ok(path) => Ok(Some(path)),
+        Err(err) if err.not_found() => Ok(None),
         Err(err) => Err(err).context("failed to initialize environment from `.env`")?,
     }
 }
 
-fn get_session() -> Result<String> {
-    match std::env::var(ADVENT_OF_CODE_SESSION) {
-        Ok(session) => Ok(session),
-        Err(VarError::NotPresent) => {
-            bail!("{ADVENT_OF_CODE_SESSION} env var required to get puzzle input")
+fn resolve_bench_duration(bench_duration: Option<f32>) -> Result<Duration> {
+    seconds_to_duration("bench duration", bench_duration.unwrap_or(1.0))
+}
+
+/// Even a short warmup gets page faults and cold caches out of the measured iterations.
+fn resolve_bench_warmup(bench_warmup_time: Option<f32>) -> Result<Duration> {
+    seconds_to_duration("bench warmup time", bench_warmup_time.unwrap_or(0.1))
+}
+
+/// Seconds come from the command line or the config, which both accept negative, NaN and infinite
+/// values.
+fn seconds_to_duration(name: &str, seconds: f32) -> Result<Duration> {
+    Duration::try_from_secs_f32(seconds).with_context(|| {
+        format!("{name} must be a finite, non-negative number of seconds, got {seconds}")
+    })
+}
+
+/// Runs every check needed for a working setup and prints the outcome as a checklist.
+fn check_env(args: &Args) -> Result<()> {
+    let mut failed = 0;
+    let mut report = |name: &str, outcome: Result<String>| match outcome {
+        Ok(details) => println!("{} {name}: {details}", Color::Green.paint("✓")),
+        Err(error) => {
+            failed += 1;
+            println!("{} {name}: {error:#}", Color::Red.paint("✗"));
         }
-        Err(error) => Err(error)?,
+    };
+
+    let session = get_session_with_source(args);
+    report(
+        "Session",
+        session
+            .as_ref()
+            .map(|(session, source)| format!("{} (from {source})", mask_secret(session)))
+            .map_err(|error| anyhow!("{error}")),
+    );
+
+    let reference = Puzzle::new(2015, 1, PuzzlePart::Part1)?;
+    let anonymous = Session::with_cookie(String::new(), args);
+    let network = reference.get_user_name(&anonymous);
+    report(
+        "Network",
+        network
+            .as_ref()
+            .map(|_| format!("{ADVENT_OF_CODE_URL} is reachable"))
+            .map_err(|error| anyhow!("{error:#}")),
+    );
+
+    if let (Ok((session, _)), Ok(_)) = (&session, &network) {
+        let session = anonymous.with_other_cookie(session.clone());
+        report(
+            "Login",
+            reference.get_user_name(&session).and_then(|user| {
+                Ok(format!(
+                    "logged in as {}",
+                    user.context("session is invalid or expired")?
+                ))
+            }),
+        );
+    }
+
+    report(
+        "Config directory",
+        calibration_path().and_then(|path| {
+            let dir = path.parent().unwrap();
+            create_dir_all(dir)?;
+            let probe = dir.join(".write-test");
+            write(&probe, "")?;
+            remove_file(&probe)?;
+            Ok(format!("{} is writable", dir.display()))
+        }),
+    );
+
+    report(
+        "Timezone",
+        Ok(format!(
+            "it is {} in Advent of Code time",
+            advent_of_code_now().format("%Y-%m-%d %H:%M %Z")
+        )),
+    );
+
+    if failed > 0 {
+        println!();
+        bail!("{failed} check(s) failed");
     }
+    Ok(())
+}
+
+fn print_config(args: &Args, dotenv_path: Option<PathBuf>, config_path: Option<PathBuf>) {
+    let [dotenv, config] = [dotenv_path, config_path].map(|path| match path {
+        Some(path) => path.display().to_string(),
+        None => "not found".to_string(),
+    });
+    let session = match get_session_with_source(args) {
+        Ok((session, source)) => format!("{} (from {source})", mask_secret(&session)),
+        Err(error) => format!("unavailable ({error})"),
+    };
+    let bench_duration = match resolve_bench_duration(args.bench.flatten()) {
+        Ok(duration) => format!("{duration:?}"),
+        Err(error) => format!("invalid ({error})"),
+    };
+    let cache = match cache_dir() {
+        Ok(dir) if args.no_cache => format!("{} (disabled)", dir.display()),
+        Ok(dir) => dir.display().to_string(),
+        Err(error) => format!("unavailable ({error})"),
+    };
+
+    println!("     .env file: {dotenv}");
+    println!("   Config file: {config}");
+    println!("       Session: {session}");
+    println!("      Base URL: {ADVENT_OF_CODE_URL}");
+    println!("   Cookie name: {}", get_cookie_name());
+    println!("Bench duration: {bench_duration}");
+    println!(" Output format: {:?}", args.format);
+    println!(" Fetch backend: {:?}", args.fetch_backend);
+    println!("    Rate limit: {:?}", resolve_rate_limit(args));
+    println!("       Timeout: {:?}", resolve_timeout(args));
+    println!("     Cache dir: {cache}");
 }
@@ -1,19 +1,45 @@
 use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::BTreeMap,
+    fs::{create_dir_all, metadata, read_to_string, rename, write, File},
     hint::black_box,
-    io::{stdout, Write},
+    io::{stdin, stdout, BufWriter, ErrorKind, IsTerminal, Read, Write},
     iter::once,
+    num::NonZeroUsize,
+    panic::{catch_unwind, set_hook, take_hook},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, OnceLock,
+    },
+    thread::{available_parallelism, scope, sleep},
     time::{Duration, Instant},
 };
 
-use anyhow::{bail, Context, Result};
-use chrono::{DateTime, Datelike, Utc};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Datelike, SecondsFormat, TimeZone, Utc};
 use chrono_tz::{Tz, EST};
 use num_traits::ToPrimitive;
-use reqwest::blocking::Client;
+
 use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
 use thousands::Separable;
 
-use crate::cmd::Args;
+use crate::{
+    allocations::{count_allocations, AllocationStats},
+    ascii::{ascii_print, ascii_println},
+    cmd::{Args, CompareMode, FetchBackend, OutputFormat, TimeUnit},
+    color::{clear_line, Color},
+    session::{Session, ADVENT_OF_CODE_SESSION},
+    stats::welch_t_test,
+};
+
+pub(crate) const ADVENT_OF_CODE_URL: &str = "https://adventofcode.com";
+pub(crate) const ADVENT_OF_CODE_CACHE_DIR: &str = "ADVENT_OF_CODE_CACHE_DIR";
+
+static ALIASES: OnceLock<BTreeMap<String, String>> = OnceLock::new();
 
 pub(crate) struct AdventOfCode<const YEAR: u32>;
 pub(crate) struct Day<const DAY: u8>;
@@ -37,33 +63,465 @@ pub(crate) enum PuzzlePart {
 pub(crate) trait Part<const N: u8> {
     const SOLUTIONS: &'static [Solution] = &[];
     const EXAMPLES: &'static [Example] = &[];
+    /// Generates a valid input of roughly the given size for `--synthetic` and `--fuzz`.
+    const GENERATOR: Option<GeneratorFn> = None;
 }
 
-#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
-pub(crate) struct Solution(pub(crate) &'static str, pub(crate) SolutionFn);
+pub(crate) type GeneratorFn = fn(size: usize) -> String;
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Solution {
+    pub(crate) name: &'static str,
+    pub(crate) solve: SolutionFn,
+    /// Formats the result for display in [`Puzzle::solve`]; examples always use [`Display`].
+    ///
+    /// [`Display`]: std::fmt::Display
+    pub(crate) format: FormatFn,
+    /// Explains the approach, e.g. its complexity; shown in benchmark comparisons.
+    pub(crate) description: Option<&'static str>,
+    /// Run when no solution is selected, instead of the first one, so the order of solutions can
+    /// change without affecting which one is run.
+    pub(crate) default: bool,
+    /// Lets benchmarks time parsing and solving separately; set by [`parsed_solution!`].
+    pub(crate) phases: Option<PhasesFn>,
+}
 
-pub(crate) type SolutionFn = fn(input: &str) -> PuzzleResult;
+/// Solves the puzzle for the input, failing on malformed input rather than panicking.
+pub(crate) type SolutionFn = fn(input: &str) -> Result<PuzzleResult>;
+pub(crate) type FormatFn = fn(result: &PuzzleResult) -> String;
+/// Runs the solution once, returning how long parsing and solving took.
+pub(crate) type PhasesFn = fn(input: &str) -> (Duration, Duration);
 
 #[allow(dead_code)]
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
 pub(crate) enum PuzzleResult {
     Int(i32),
+    Int64(i64),
+    UInt64(u64),
     Str(String),
+    /// Letters drawn with `#` pixels that have to be read, see [`PuzzleResult::read_letters`].
+    Grid(Vec<String>),
+}
+
+impl From<i32> for PuzzleResult {
+    fn from(value: i32) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<i64> for PuzzleResult {
+    fn from(value: i64) -> Self {
+        Self::Int64(value)
+    }
+}
+
+impl From<u64> for PuzzleResult {
+    fn from(value: u64) -> Self {
+        Self::UInt64(value)
+    }
 }
 
+impl From<String> for PuzzleResult {
+    fn from(value: String) -> Self {
+        Self::Str(value)
+    }
+}
+
+impl From<Vec<String>> for PuzzleResult {
+    fn from(value: Vec<String>) -> Self {
+        Self::Grid(value)
+    }
+}
+
+const LETTER_WIDTH: usize = 4;
+const LETTER_HEIGHT: usize = 6;
+
+/// The letters of the font used by grid puzzles, as far as they appeared in any of them.
+const LETTERS: &[(char, [&str; LETTER_HEIGHT])] = &[
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
-pub(crate) struct Example(pub(crate) usize, pub(crate) usize);
+pub(crate) struct Example {
+    /// Index of the code block holding the input.
+    pub(crate) input: usize,
+    /// Where the expected result comes from.
+    pub(crate) expected_result: ExpectedResult,
+    /// Compares integer results numerically with an expected result written in this base.
+    pub(crate) base: Option<u32>,
+    /// The input refers to a `<table>` on the puzzle page rather than a code block.
+    pub(crate) table_input: bool,
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum ExpectedResult {
+    /// Index of the code block holding the expected result.
+    CodeBlock(usize),
+    /// The expected result itself, e.g. if the puzzle only states it in prose.
+    Literal(&'static str),
+}
+
+/// Text blocks scraped from the puzzle page that examples refer to by index.
+struct ExampleBlocks {
+    code_blocks: Vec<String>,
+    /// Tables normalized to one line per row with cells separated by a space.
+    tables: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct SolveRecord<'a> {
+    year: u32,
+    day: u8,
+    part: u8,
+    solution: &'a str,
+    result: &'a PuzzleResult,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BenchmarkSettings {
+    /// Run the solution without measuring for this long first, e.g. to fill caches.
+    pub(crate) warmup: Duration,
+    pub(crate) duration: Duration,
+    /// Additionally measure CPU cycles using the timestamp counter; requires x86_64.
+    pub(crate) cycles: bool,
+    pub(crate) allow_small_input: bool,
+    /// Discard iterations that take much longer than the median, e.g. due to preemption.
+    pub(crate) reject_outliers: bool,
+    /// Keep running past the duration until this many iterations are collected.
+    pub(crate) min_iterations: Option<usize>,
+    /// Stop after this many iterations, even before the duration is over, which is
+    /// [`Duration::MAX`] if only the iterations limit the benchmark.
+    pub(crate) iterations: Option<usize>,
+    /// Ignore this percentage of both the fastest and slowest iterations for Avg and StdDev.
+    pub(crate) trim: Option<f64>,
+    /// How results of different solutions are checked against each other.
+    pub(crate) compare_mode: CompareMode,
+    /// Benchmark a generated input of this size instead of the real input.
+    pub(crate) synthetic: Option<usize>,
+    /// Test whether the two fastest solutions in a comparison differ significantly.
+    pub(crate) significance: bool,
+    /// Scale all times by this calibration factor of the machine.
+    pub(crate) normalization: Option<f64>,
+    /// Archive every run as a JSON file in this directory.
+    pub(crate) output_dir: Option<PathBuf>,
+    /// Which unit times in the results are shown in.
+    pub(crate) unit: TimeUnit,
+    /// Count the allocations of a single run before benchmarking; comparisons then run one
+    /// solution at a time, since the counters are shared by all threads.
+    pub(crate) allocations: bool,
+}
+
+/// Golden results recorded by `--snapshot`, relative to the working directory.
+const SNAPSHOT_DIR: &str = "snapshots";
+
+/// Answers accepted by Advent of Code for `--verify`, relative to the working directory.
+const ANSWERS_FILE: &str = "answers.json";
+
+/// Answers by year, day and part.
+type Answers = BTreeMap<u32, BTreeMap<u8, BTreeMap<u8, String>>>;
+
+/// Comparisons estimated to take at least this long ask for confirmation when run interactively.
+const CONFIRM_ESTIMATE_THRESHOLD: Duration = Duration::from_secs(30);
+
+/// Hard limit on how long a minimum iteration count may extend a benchmark.
+pub(crate) const MIN_ITERATIONS_TIME_CAP: Duration = Duration::from_secs(60);
+
+/// How long the reference workload is benchmarked by `--calibrate`.
+const CALIBRATION_DURATION: Duration = Duration::from_secs(3);
+/// Size of the generated input for the reference workload.
+const CALIBRATION_INPUT_SIZE: usize = 100_000;
+/// Normalized times are scaled as if the reference workload took exactly this long.
+const CALIBRATION_BASELINE: Duration = Duration::from_micros(100);
+
+#[derive(Deserialize, Serialize)]
+struct Calibration {
+    factor: f64,
+    reference_ns: u128,
+}
+
+/// Differences with a p-value below this are considered significant.
+const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+/// Iterations slower than this multiple of the median are rejected as outliers.
+const OUTLIER_FACTOR: u32 = 10;
+
+/// Stack samples taken per second while profiling.
+#[cfg(feature = "profile")]
+const PROFILE_FREQUENCY: i32 = 1000;
+/// Number of locations listed in the profile.
+#[cfg(feature = "profile")]
+const PROFILE_TOP_N: usize = 10;
+
+/// Inputs smaller than this are most likely examples rather than real puzzle inputs.
+const SMALL_INPUT_THRESHOLD: usize = 100;
+
+/// How many bytes of each block `--list-blocks` shows.
+const BLOCK_PREVIEW_LEN: usize = 60;
+
+/// How long a cached calendar without the star is trusted, e.g. after solving on the website.
+const CALENDAR_TTL: Duration = Duration::from_secs(15 * 60);
+
+pub(crate) const CYCLE_COUNTER_AVAILABLE: bool = cfg!(target_arch = "x86_64");
+
+#[derive(Serialize)]
+struct BenchmarkRun<'a> {
+    year: u32,
+    day: u8,
+    part: u8,
+    timestamp: String,
+    results: Vec<BenchmarkRecord<'a>>,
+}
+
+/// A [`BenchmarkRecord`] together with the puzzle it was run on, for `--format jsonl`.
+#[derive(Serialize)]
+struct BenchmarkLine<'a> {
+    year: u32,
+    day: u8,
+    part: u8,
+    #[serde(flatten)]
+    record: BenchmarkRecord<'a>,
+}
+
+/// Serializable form of a [`BenchmarkResult`] with all durations in nanoseconds.
+#[derive(Serialize)]
+struct BenchmarkRecord<'a> {
+    solution: &'a str,
+    iterations: usize,
+    rejected: usize,
+    runtime_ns: u128,
+    overhead_ns: u128,
+    average_ns: u128,
+    std_dev_ns: u128,
+    min_ns: u128,
+    med_ns: u128,
+    max_ns: u128,
+    cycles: Option<&'a CycleStats>,
+}
 
 struct BenchmarkResult {
+    warmup: Duration,
+    warmup_iterations: usize,
     runtime: Duration,
     overhead: Duration,
     iterations: usize,
+    rejected: usize,
+    /// The benchmark ran past its duration to reach the minimum number of iterations.
+    extended: bool,
     average: Duration,
     std_dev: Duration,
     min: Duration,
     med: Duration,
     max: Duration,
+    cycles: Option<CycleStats>,
+    /// Only for solutions that parse the input separately.
+    phases: Option<PhaseStats>,
+    allocations: Option<AllocationStats>,
+    /// The individual iteration times, sorted.
+    times: Vec<Duration>,
+}
+
+#[derive(Serialize)]
+struct CycleStats {
+    average: u64,
+    min: u64,
+    med: u64,
+    max: u64,
+}
+
+/// Average times of parsing the input and solving the puzzle with the parsed input.
+struct PhaseStats {
+    parse: Duration,
+    solve: Duration,
+}
+
+impl BenchmarkSettings {
+    pub(crate) fn print_explanation(&self, compare: bool) {
+        ascii_println!("How this benchmark works:");
+        if !self.warmup.is_zero() {
+            ascii_println!(
+                "- Each solution is first warmed up for {:.2?} without measuring.",
+                self.warmup
+            );
+        }
+        ascii_print!(
+            "- Each solution is run back to back on the input {}",
+            self.describe_measuring()
+        );
+        if let Some(min_iterations) = self.min_iterations {
+            ascii_print!(
+                ", or until {} iterations were collected (at most {MIN_ITERATIONS_TIME_CAP:.0?})",
+                min_iterations.separate_with_commas()
+            );
+        }
+        ascii_println!(".");
+        ascii_println!(
+            "- Iterations is the number of times the solution ran; each one is timed individually."
+        );
+        ascii_println!("- Overhead is the time spent outside of the solution itself, e.g. on timing and bookkeeping.");
+        if self.reject_outliers {
+            ascii_println!("- Iterations slower than {OUTLIER_FACTOR}× the current median estimate are rejected and excluded from all statistics.");
+        }
+        ascii_println!(
+            "- Avg is the total time spent in the solution divided by the number of iterations."
+        );
+        if let Some(trim) = self.trim {
+            ascii_println!("- Avg and StdDev ignore the fastest and slowest {trim}% of iterations, while Min, Med and Max still include them.");
+        }
+        ascii_println!(
+            "- StdDev is the spread of the individual iteration times around the average."
+        );
+        ascii_println!("- Min, Med and Max are the fastest, median and slowest iteration times.");
+        if self.cycles {
+            ascii_println!(
+                "- Cycles are read from the CPU's timestamp counter around each iteration."
+            );
+        }
+        if self.allocations {
+            ascii_println!("- Allocations are counted during a single run before the benchmark, along with the total and peak number of allocated bytes.");
+        }
+        if compare {
+            ascii_println!(
+                "- Relative is how much slower a solution is on average than the fastest one."
+            );
+            ascii_println!(
+                "- Solutions returning a different result than the first one are grayed out."
+            );
+            if self.allocations {
+                ascii_println!("- Solutions are benchmarked one at a time, since allocations are counted for the whole process.");
+            } else {
+                ascii_println!(
+                    "- Solutions are benchmarked in parallel, one per available CPU core."
+                );
+            }
+        }
+        println!();
+    }
+
+    /// How long each solution is measured, e.g. `for 1.00s` or `1,000 times`.
+    fn describe_measuring(&self) -> String {
+        match self.iterations {
+            None => format!("for {:.2?}", self.duration),
+            Some(iterations) if self.duration == Duration::MAX => {
+                format!("{} times", iterations.separate_with_commas())
+            }
+            Some(iterations) => format!(
+                "for {:.2?} or {} times, whichever comes first",
+                self.duration,
+                iterations.separate_with_commas()
+            ),
+        }
+    }
+
+    /// Whether a benchmark that collected `iterations` over `elapsed` is done, which for a minimum
+    /// number of iterations can be after the duration.
+    fn is_done(&self, elapsed: Duration, iterations: usize) -> bool {
+        if self
+            .iterations
+            .is_some_and(|max_iterations| iterations >= max_iterations)
+        {
+            return true;
+        }
+        let below_min_iterations = self
+            .min_iterations
+            .is_some_and(|min_iterations| iterations < min_iterations);
+        elapsed >= self.duration && (!below_min_iterations || elapsed >= MIN_ITERATIONS_TIME_CAP)
+    }
+
+    /// Estimates how long benchmarking takes for a solution that runs for `iteration` once.
+    fn estimate_duration(&self, iteration: Duration) -> Duration {
+        let floor = self
+            .min_iterations
+            .map(|min_iterations| {
+                iteration
+                    .saturating_mul(min_iterations.try_into().unwrap_or(u32::MAX))
+                    .min(MIN_ITERATIONS_TIME_CAP)
+            })
+            .unwrap_or_default();
+        let mut measuring = iteration.max(self.duration).max(floor);
+        if let Some(iterations) = self.iterations {
+            measuring =
+                measuring.min(iteration.saturating_mul(iterations.try_into().unwrap_or(u32::MAX)));
+        }
+        self.warmup.saturating_add(measuring)
+    }
+}
+
+impl Solution {
+    pub(crate) const fn new(name: &'static str, solve: SolutionFn) -> Self {
+        Self {
+            name,
+            solve,
+            format: |result| result.to_string(),
+            description: None,
+            default: false,
+            phases: None,
+        }
+    }
+
+    /// Lets benchmarks time parsing and solving separately; prefer [`parsed_solution!`].
+    pub(crate) const fn with_phases(self, phases: PhasesFn) -> Self {
+        Self {
+            phases: Some(phases),
+            ..self
+        }
+    }
+
+    /// Marks this as the solution that is run unless another one is selected.
+    pub(crate) const fn as_default(self) -> Self {
+        Self {
+            default: true,
+            ..self
+        }
+    }
+
+    pub(crate) const fn with_description(self, description: &'static str) -> Self {
+        Self {
+            description: Some(description),
+            ..self
+        }
+    }
+
+    #[allow(dead_code)]
+    pub(crate) const fn with_format(self, format: FormatFn) -> Self {
+        Self { format, ..self }
+    }
+}
+
+/// Declares a [`Solution`] that first parses the input with `$parse` and then passes a reference
+/// to the parsed input to `$solve`, so benchmarks can time both phases separately.
+macro_rules! parsed_solution {
+    ($name:expr, $parse:expr, $solve:expr $(,)?) => {
+        $crate::puzzle::Solution::new($name, |input| ($solve)(&($parse)(input)?)).with_phases(
+            |input| {
+                let start = ::std::time::Instant::now();
+                let parsed = ::std::hint::black_box(($parse)(::std::hint::black_box(input)));
+                let parse = start.elapsed();
+                let start = ::std::time::Instant::now();
+                if let Ok(parsed) = &parsed {
+                    let _ = ::std::hint::black_box(($solve)(parsed));
+                }
+                (parse, start.elapsed())
+            },
+        )
+    };
 }
+pub(crate) use parsed_solution;
 
 impl Puzzle {
     pub(crate) fn from_args(args: &Args) -> Result<Self> {
@@ -82,6 +540,14 @@ impl Puzzle {
                 if now.month() != 12 {
                     bail!("Current Day can only be deduced in December; please specify");
                 }
+                if args.verbose {
+                    println!(
+                        "No day specified; using current day {} of {} (December detected)",
+                        now.day(),
+                        now.year()
+                    );
+                    println!();
+                }
                 Puzzle::new(now.year(), now.day(), part)
             }
             Args {
@@ -90,11 +556,18 @@ impl Puzzle {
                 ..
             } => {
                 let now = advent_of_code_now();
-                Puzzle::new(
-                    now.year() - if now.month() < 12 { 1 } else { 0 },
-                    *day,
-                    part,
-                )
+                let year = now.year() - if now.month() < 12 { 1 } else { 0 };
+                if args.verbose {
+                    if now.month() < 12 {
+                        println!("No year specified; using last year {year} (not December yet)");
+                    } else {
+                        println!(
+                            "No year specified; using current year {year} (December detected)"
+                        );
+                    }
+                    println!();
+                }
+                Puzzle::new(year, *day, part)
             }
             Args {
                 year: Some(year),
@@ -109,7 +582,11 @@ impl Puzzle {
         }
     }
 
-    fn new(year: impl ToPrimitive, day: impl ToPrimitive, part: PuzzlePart) -> Result<Self> {
+    pub(crate) fn new(
+        year: impl ToPrimitive,
+        day: impl ToPrimitive,
+        part: PuzzlePart,
+    ) -> Result<Self> {
         Ok(Self {
             year: year
                 .to_u32()
@@ -124,292 +601,2780 @@ impl Puzzle {
     }
 
     fn puzzle_url(&self) -> String {
-        format!("https://adventofcode.com/{}/day/{}", self.year, self.day)
+        format!("{ADVENT_OF_CODE_URL}/{}/day/{}", self.year, self.day)
     }
 
-    fn input_url(&self) -> String {
-        format!("{}/input", self.puzzle_url())
+    fn year_url(&self) -> String {
+        format!("{ADVENT_OF_CODE_URL}/{}", self.year)
     }
 
-    fn get_with_session(&self, session: &str, url: &str) -> Result<String> {
-        Ok(Client::builder()
-            .build()?
-            .get(url)
-            .header("cookie", format!("session={session}"))
-            .send()?
-            .text()?)
+    fn answer_url(&self) -> String {
+        format!("{}/answer", self.puzzle_url())
     }
 
-    fn get_input(&self, session: &str) -> Result<String> {
-        self.get_with_session(session, &self.input_url())
+    fn input_url(&self) -> String {
+        format!("{}/input", self.puzzle_url())
     }
 
-    fn get_code_blocks(&self, session: &str) -> Result<Vec<String>> {
-        Html::parse_document(&self.get_with_session(session, &self.puzzle_url())?)
-            .select(&Selector::parse("code").unwrap())
-            .map(|element| {
-                Ok(element
-                    .text()
-                    .next()
-                    .context("malformed example")?
-                    .to_string())
-            })
-            .collect()
+    /// Retries timeouts, connection errors and server errors with exponential backoff.
+    fn get_with_session(&self, session: &Session, url: &str) -> Result<String> {
+        if session.dry_network {
+            return Ok(String::new());
+        }
+        let mut backoff = INITIAL_RETRY_BACKOFF;
+        for attempt in 1.. {
+            session.wait_for_rate_limit();
+            match self.try_get_with_session(session, url) {
+                Err(error) if attempt < MAX_REQUEST_ATTEMPTS && is_transient(&error) => {
+                    eprintln!(
+                        "{}",
+                        Color::Yellow.paint(format!(
+                            "WARNING: {error}, retrying in {backoff:?} (attempt {} of {MAX_REQUEST_ATTEMPTS})",
+                            attempt + 1
+                        ))
+                    );
+                    sleep(backoff);
+                    backoff *= 2;
+                }
+                result => return result,
+            }
+        }
+        unreachable!()
     }
 
-    pub(crate) fn print_header(&self) {
-        println!(
-            "Advent of Code {} - Day {} - {}",
-            self.year,
-            self.day,
-            match self.part {
-                PuzzlePart::Part1 => "Part 1",
-                PuzzlePart::Part2 => "Part 2",
+    fn try_get_with_session(&self, session: &Session, url: &str) -> Result<String> {
+        match session.fetch_backend {
+            FetchBackend::Reqwest => {
+                let response = session
+                    .client()?
+                    .get(url)
+                    .header("cookie", session.cookie_header())
+                    .send()?;
+                let status = response.status();
+                // A malformed session makes Advent of Code fail rather than treat it as logged out.
+                if status.is_server_error() {
+                    bail!(TransientError(format!("{url} responded with {status}; if this persists, the session might be malformed, refresh {ADVENT_OF_CODE_SESSION}")));
+                }
+                Ok(response.text()?)
             }
-        );
-        println!();
+            FetchBackend::Curl => get_with_curl(&session.cookie_header(), url, session.timeout),
+        }
     }
 
-    pub(crate) fn get_input_verbose(&self, session: &str) -> Result<String> {
-        print!("Grabbing input... ");
-        stdout().flush()?;
-        let input = self.get_input(session)?;
-        println!("got {} bytes.", input.len());
-        println!();
-        Ok(input)
+    /// Never retried, as a submitted answer might have arrived even if the response did not.
+    fn post_with_session(
+        &self,
+        session: &Session,
+        url: &str,
+        form: &[(&str, &str)],
+    ) -> Result<String> {
+        if session.dry_network {
+            return Ok(String::new());
+        }
+        session.wait_for_rate_limit();
+        match session.fetch_backend {
+            FetchBackend::Reqwest => Ok(session
+                .client()?
+                .post(url)
+                .header("cookie", session.cookie_header())
+                .form(form)
+                .send()?
+                .text()?),
+            FetchBackend::Curl => {
+                post_with_curl(&session.cookie_header(), url, form, session.timeout)
+            }
+        }
     }
 
-    pub(crate) fn solve(&self, solution: Option<&str>, session: &str) -> Result<()> {
-        let Solution(_, solve) = self.get_solution(solution)?;
-        let input = self.get_input_verbose(session)?;
-        let result = solve(&input);
-        println!("{}", result);
-        Ok(())
+    fn get_input(&self, session: &Session) -> Result<String> {
+        Ok(self.get_input_with_source(session)?.0)
     }
 
-    pub(crate) fn run_examples(
-        &self,
-        solution: Option<&str>,
-        session: &str,
-        examples: impl Iterator<Item = Example>,
-    ) -> Result<()> {
-        let Solution(_, solve) = self.get_solution(solution)?;
+    /// Reads the input from `--input`, the cache or downloads and caches it, also returning where
+    /// the input came from.
+    fn get_input_with_source(&self, session: &Session) -> Result<(String, Cow<'static, str>)> {
+        if let Some(input_path) = &session.input_path {
+            if input_path == Path::new("-") {
+                let mut input = String::new();
+                stdin()
+                    .read_to_string(&mut input)
+                    .context("failed to read input from stdin")?;
+                return Ok((input, "stdin".into()));
+            }
+            let input = read_to_string(input_path)
+                .with_context(|| format!("failed to read `{}`", input_path.display()))?;
+            return Ok((input, input_path.display().to_string().into()));
+        }
 
-        print!("Scraping Example Inputs... ");
-        stdout().flush()?;
-        let code_blocks = self.get_code_blocks(session)?;
-        println!("Done!");
-        println!();
+        if session.dry_network {
+            return Ok((
+                self.get_with_session(session, &self.input_url())?,
+                "network".into(),
+            ));
+        }
 
-        let mut success = 0;
-        let mut total = 0;
-        println!("| Running Examples... ");
-        println!("|---------------------");
-        for Example(input_offset, expected_result_offset) in examples {
-            total += 1;
-            let input = code_blocks
-                .get(input_offset)
-                .context("example offset out of bounds")?;
-            let expected_result = code_blocks
-                .get(expected_result_offset)
-                .context("expected result offset out of bounds")?;
-            let result = solve(input);
-            if &format!("{}", result) == expected_result {
-                println!("| Example #{total} passed");
-                success += 1;
-            } else {
-                println!("| Example #{total} failed: {expected_result} != {result}");
-                println!("|- Input: {input}");
+        let path = self.cache_dir()?.join("input.txt");
+        if session.use_cache {
+            match read_to_string(&path) {
+                Ok(input) => return Ok((input, "cache".into())),
+                Err(error) if error.kind() == ErrorKind::NotFound => {}
+                Err(error) => {
+                    return Err(error)
+                        .with_context(|| format!("failed to read `{}`", path.display()))
+                }
             }
         }
-        if total > 0 {
-            println!("|---------------------");
-            println!("| {success} / {total} Examples passed");
-        } else {
-            println!("| No Examples found");
+
+        let mut input = self.get_with_session(session, &self.input_url())?;
+        // The servers can take a few seconds to release the puzzle after the countdown ends.
+        if session.wait && input.starts_with(NOT_UNLOCKED_RESPONSE) {
+            eprintln!("Input not available yet, retrying in {UNLOCK_RETRY_DELAY:?}...");
+            sleep(UNLOCK_RETRY_DELAY);
+            input = self.get_with_session(session, &self.input_url())?;
         }
-        Ok(())
+        check_input_response(&input)?;
+        write_atomically(&path, &input)?;
+        Ok((input, "network".into()))
     }
 
-    pub(crate) fn print_benchmark(
-        &self,
-        solution: Option<&str>,
-        session: &str,
-        bench_duration: Duration,
-    ) -> Result<()> {
-        let Solution(_, solve) = self.get_solution(solution)?;
-        let input = self.get_input_verbose(session)?;
+    /// The directory in which inputs and other data of this puzzle are cached.
+    fn cache_dir(&self) -> Result<PathBuf> {
+        Ok(cache_dir()?
+            .join(self.year.to_string())
+            .join(format!("day_{}", self.day)))
+    }
 
-        let BenchmarkResult {
-            runtime,
-            overhead,
-            iterations,
-            average,
-            std_dev,
-            min,
-            med,
-            max,
-        } = self.benchmark(solve, &input, bench_duration);
+    /// Reads the puzzle page from the cache or downloads and caches it, also returning whether the
+    /// cache was used.
+    fn get_puzzle_page(&self, session: &Session) -> Result<(String, bool)> {
+        if session.dry_network {
+            return Ok((self.get_with_session(session, &self.puzzle_url())?, false));
+        }
 
-        println!("Benchmark ran for {runtime:.2?} (plus {overhead:.2?} of overhead)");
-        println!("  Iterations: {}", iterations.separate_with_commas());
-        println!("  Avg±StdDev: {average:.2?} ± {std_dev:.2?}");
-        println!(" Min<Med<Max: {min:.2?} < {med:.2?} < {max:.2?}");
-        println!();
+        let path = self.cache_dir()?.join("puzzle.html");
+        if session.use_cache {
+            if let Some(page) = self.read_cached_puzzle_page(&path)? {
+                return Ok((page, true));
+            }
+        }
 
-        Ok(())
+        let page = self.get_with_session(session, &self.puzzle_url())?;
+        if count_puzzle_parts(&Html::parse_document(&page)) > 0 {
+            write_atomically(&path, &page)?;
+        }
+        Ok((page, false))
     }
 
-    pub(crate) fn print_benchmark_comparison(
-        &self,
-        session: &str,
-        bench_duration: Duration,
-    ) -> Result<()> {
-        let input = self.get_input_verbose(session)?;
-
-        let solutions = self.get_solutions();
-        if solutions.is_empty() {
-            bail!("puzzle has no solutions");
+    /// Returns [`None`] if the cache is missing, older than the puzzle, incomplete, e.g. because
+    /// writing it was interrupted, or doesn't show part 2 yet while that is what's requested.
+    fn read_cached_puzzle_page(&self, path: &Path) -> Result<Option<String>> {
+        let modified = match metadata(path).and_then(|metadata| metadata.modified()) {
+            Ok(modified) => DateTime::<Utc>::from(modified),
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => {
+                return Err(error).with_context(|| format!("failed to read `{}`", path.display()))
+            }
+        };
+        if modified < self.release_time() {
+            return Ok(None);
         }
+        let page =
+            read_to_string(path).with_context(|| format!("failed to read `{}`", path.display()))?;
+        if !page.trim_end().ends_with("</html>") {
+            return Ok(None);
+        }
+        if self.part == PuzzlePart::Part2 && count_puzzle_parts(&Html::parse_document(&page)) < 2 {
+            return Ok(None);
+        }
+        Ok(Some(page))
+    }
 
-        const SOLUTION: &str = "Solution";
-        let name_width = solutions
-            .iter()
-            .map(|Solution(name, _)| name.len())
-            .chain(once(SOLUTION.len()))
-            .max()
-            .unwrap();
-
-        let mut benchmark_results = solutions
-            .iter()
-            .copied()
-            .enumerate()
-            .inspect(|(i, Solution(name, _))| {
-                print!(
-                    "\r\x1b[KBenchmarking {}/{} - {name}",
-                    i + 1,
-                    solutions.len(),
-                );
-                stdout().flush().unwrap();
-            })
-            .map(|(_, Solution(name, solve))| {
-                (
-                    name,
-                    solve(&input),
-                    self.benchmark(solve, &input, bench_duration),
-                )
+    /// Scrapes the example blocks from the puzzle page, also returning whether the page was cached.
+    fn get_example_blocks(&self, session: &Session) -> Result<(ExampleBlocks, bool)> {
+        let (page, cached) = self.get_puzzle_page(session)?;
+        let page = Html::parse_document(&page);
+        // Part 2 is only shown once part 1 is solved; offsets would point at the wrong blocks.
+        if self.part == PuzzlePart::Part2 && count_puzzle_parts(&page) < 2 {
+            bail!("part 2 not unlocked yet; solve part 1 first");
+        }
+        let code_blocks = scrape_code_blocks(&page);
+        let row_selector = Selector::parse("tr").unwrap();
+        let cell_selector = Selector::parse("th, td").unwrap();
+        let tables = page
+            .select(&Selector::parse("table").unwrap())
+            .map(|table| {
+                table
+                    .select(&row_selector)
+                    .map(|row| {
+                        row.select(&cell_selector)
+                            .map(|cell| cell.text().collect::<String>().trim().to_string())
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
             })
-            .collect::<Vec<_>>();
-        print!("\r\x1b[2K");
-
-        let first_puzzle_result = benchmark_results.first().unwrap().1.clone();
-
-        benchmark_results.sort_by_key(|(_, _, result)| result.average);
-
-        let fastest_time = benchmark_results[0].2.average;
+            .collect();
+        Ok((
+            ExampleBlocks {
+                code_blocks,
+                tables,
+            },
+            cached,
+        ))
+    }
 
-        const WS: &str = "";
+    /// Puzzles unlock at midnight EST.
+    fn release_time(&self) -> DateTime<Utc> {
+        EST.with_ymd_and_hms(
+            u32::from(self.year) as i32,
+            12,
+            u8::from(self.day).into(),
+            0,
+            0,
+            0,
+        )
+        .unwrap()
+        .with_timezone(&Utc)
+    }
 
-        println!("  {WS: <name_width$} ┏━━ Averge ±   StdDev ┯ Relative ┳━ Mininum ┯━━ Median ┯━ Maximum ┓");
-        println!("┏━{WS:━<name_width$}━╋━━━━━━━━━━━━━━━━━━━━━┿━━━━━━━━━━╋━━━━━━━━━━┿━━━━━━━━━━┿━━━━━━━━━━┫");
+    /// Fails with the remaining time if the puzzle is not unlocked yet, or counts down until it
+    /// unlocks with `wait`, so no requests are made for a puzzle that doesn't exist yet.
+    pub(crate) fn wait_for_release(&self, wait: bool) -> Result<()> {
+        let Some(remaining) = self.time_until_release() else {
+            return Ok(());
+        };
+        if !wait {
+            bail!(
+                "puzzle unlocks in {}; pass --wait to wait for it",
+                format_countdown(remaining)
+            );
+        }
 
-        for (
-            name,
-            puzzle_result,
-            BenchmarkResult {
-                average,
-                std_dev,
-                min,
-                med,
-                max,
-                ..
-            },
-        ) in &benchmark_results
-        {
-            let wrong = puzzle_result != &first_puzzle_result;
-            let rel = (average.as_secs_f32() / fastest_time.as_secs_f32() - 1.0) * 100.0;
-            if wrong {
-                print!("\x1b[90m");
-            }
-            print!("┃ {name:<name_width$} ┃ {average:>8.2?} ± {std_dev:>8.2?} │ {rel:>7.1}% ┃ {min:>8.2?} │ {med:>8.2?} │ {max:>8.2?} ┃");
-            if wrong {
-                print!(" \x1b[33m{puzzle_result} != {first_puzzle_result}\x1b[0m");
-            }
+        if !stdout().is_terminal() {
+            println!(
+                "Waiting {} for the puzzle to unlock...",
+                format_countdown(remaining)
+            );
+            sleep(remaining);
             println!();
+            return Ok(());
         }
 
-        println!("┗━{WS:━<name_width$}━┻━━━━━━━━━━━━━━━━━━━━━┷━━━━━━━━━━┻━━━━━━━━━━┷━━━━━━━━━━┷━━━━━━━━━━┛");
-
+        // Recomputed on every tick, so the countdown can't drift from the actual unlock time.
+        while let Some(remaining) = self.time_until_release() {
+            print!(
+                "\r{}Puzzle unlocks in {}...",
+                clear_line(),
+                format_countdown(remaining)
+            );
+            stdout().flush()?;
+            let until_next_second = Duration::from_nanos(remaining.subsec_nanos().into());
+            sleep(if until_next_second.is_zero() {
+                remaining.min(Duration::from_secs(1))
+            } else {
+                until_next_second
+            });
+        }
+        println!("\r{}Puzzle unlocked!", clear_line());
+        println!();
         Ok(())
     }
 
-    fn benchmark(
-        &self,
-        solve: SolutionFn,
-        input: &str,
-        bench_duration: Duration,
-    ) -> BenchmarkResult {
-        // Using Vec and then sort to minimize overhead compared to e.g. BTreeSet.
-        // Pre-allocating some capacity doesn't make much difference and picking a good initial
+    /// Based on the time of Advent of Code, [`None`] once the puzzle is unlocked.
+    fn time_until_release(&self) -> Option<Duration> {
+        self.release_time()
+            .signed_duration_since(advent_of_code_now())
+            .to_std()
+            .ok()
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Checks the calendar on the year page for the star of this puzzle part, unless an accepted
+    /// answer is already recorded.
+    ///
+    /// Returns [`None`] if only a download could tell and `may_download` is false.
+    fn has_star(&self, session: &Session, may_download: bool) -> Result<Option<bool>> {
+        if self.accepted_answer_path()?.exists() {
+            return Ok(Some(true));
+        }
+        let Some(page) = self.get_calendar_page(session, may_download)? else {
+            return Ok(None);
+        };
+        self.calendar_shows_star(&Html::parse_document(&page))
+            .map(Some)
+    }
+
+    fn calendar_shows_star(&self, calendar: &Html) -> Result<bool> {
+        let day = calendar
+            .select(&self.calendar_day_selector())
+            .next()
+            .context("day not found in calendar")?;
+        let mut classes = day.value().classes();
+        Ok(match self.part {
+            PuzzlePart::Part1 => classes
+                .any(|class| class == "calendar-complete" || class == "calendar-verycomplete"),
+            PuzzlePart::Part2 => classes.any(|class| class == "calendar-verycomplete"),
+        })
+    }
+
+    fn calendar_day_selector(&self) -> Selector {
+        Selector::parse(&format!(".calendar-day{}", self.day)).unwrap()
+    }
+
+    fn calendar_path(&self) -> Result<PathBuf> {
+        Ok(cache_dir()?
+            .join(self.year.to_string())
+            .join("calendar.html"))
+    }
+
+    /// Reads the year page from the cache or, if `may_download`, downloads and caches it.
+    ///
+    /// Stars are only a nice to have, so the download is a single attempt without any warnings.
+    /// The cache is dropped whenever an answer is accepted, or else refreshed with `--no-cache`.
+    fn get_calendar_page(&self, session: &Session, may_download: bool) -> Result<Option<String>> {
+        if session.dry_network {
+            return Ok(None);
+        }
+
+        let path = self.calendar_path()?;
+        if session.use_cache {
+            if let Some(page) = self.read_cached_calendar_page(&path)? {
+                return Ok(Some(page));
+            }
+        }
+        if !may_download {
+            return Ok(None);
+        }
+
+        session.wait_for_rate_limit();
+        let page = self.try_get_with_session(session, &self.year_url())?;
+        // Only cache a calendar that already shows this day, i.e. not when logged out.
+        if Html::parse_document(&page)
+            .select(&self.calendar_day_selector())
+            .next()
+            .is_some()
+        {
+            write_atomically(&path, &page)?;
+        }
+        Ok(Some(page))
+    }
+
+    /// Returns [`None`] if the cache is missing, older than the puzzle or incomplete.
+    ///
+    /// A star never goes away, but one can be earned without this tool, so a calendar without it
+    /// expires after [`CALENDAR_TTL`].
+    fn read_cached_calendar_page(&self, path: &Path) -> Result<Option<String>> {
+        let modified = match metadata(path).and_then(|metadata| metadata.modified()) {
+            Ok(modified) => modified,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => {
+                return Err(error).with_context(|| format!("failed to read `{}`", path.display()))
+            }
+        };
+        if DateTime::<Utc>::from(modified) < self.release_time() {
+            return Ok(None);
+        }
+        let page =
+            read_to_string(path).with_context(|| format!("failed to read `{}`", path.display()))?;
+        if !page.trim_end().ends_with("</html>") {
+            return Ok(None);
+        }
+        let expired = modified.elapsed().is_ok_and(|age| age > CALENDAR_TTL);
+        if expired && !self.calendar_shows_star(&Html::parse_document(&page))? {
+            return Ok(None);
+        }
+        Ok(Some(page))
+    }
+
+    /// Forgets the cached calendar, so it picks up a newly earned star.
+    fn clear_cached_calendar_page(&self) -> Result<()> {
+        let path = self.calendar_path()?;
+        match std::fs::remove_file(&path) {
+            Err(error) if error.kind() != ErrorKind::NotFound => {
+                Err(error).with_context(|| format!("failed to remove `{}`", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Scrapes the name of the logged in user from the header of the year page.
+    pub(crate) fn get_user_name(&self, session: &Session) -> Result<Option<String>> {
+        let page = Html::parse_document(&self.get_with_session(session, &self.year_url())?);
+        Ok(page
+            .select(&Selector::parse("header .user").unwrap())
+            .next()
+            .and_then(|user| user.text().next())
+            .map(|name| name.trim().to_string()))
+    }
+
+    fn print_star(&self, session: &Session) {
+        // A local input may not even belong to the session, if there is one at all.
+        if session.input_path.is_some() {
+            return;
+        }
+        // Downloading the calendar ahead of the input would wait for the rate limit in between.
+        let input_cached = session.use_cache
+            && self
+                .cache_dir()
+                .is_ok_and(|dir| dir.join("input.txt").exists());
+        if let Ok(Some(earned)) = self.has_star(session, input_cached) {
+            if earned {
+                println!("(★ already earned)");
+            } else {
+                println!("(not yet solved)");
+            }
+            println!();
+        }
+    }
+
+    fn get_example_blocks_verbose(&self, session: &Session) -> Result<ExampleBlocks> {
+        print!("Scraping Example Inputs... ");
+        stdout().flush()?;
+        let (example_blocks, cached) = self.get_example_blocks(session)?;
+        println!(
+            "{}",
+            if cached {
+                "Done (from cache)!"
+            } else {
+                "Done!"
+            }
+        );
+        println!();
+        Ok(example_blocks)
+    }
+
+    /// Prints every code block and table that examples can refer to along with its index.
+    pub(crate) fn list_blocks(&self, session: &Session) -> Result<()> {
+        let ExampleBlocks {
+            code_blocks,
+            tables,
+        } = self.get_example_blocks_verbose(session)?;
+        println!("Code blocks:");
+        for (index, block) in code_blocks.iter().enumerate() {
+            println!("{index:>4}: {:?}", truncate_input(block, BLOCK_PREVIEW_LEN));
+        }
+        if !tables.is_empty() {
+            println!();
+            println!("Tables, for examples using `with_table_input`:");
+            for (index, table) in tables.iter().enumerate() {
+                println!("{index:>4}: {:?}", truncate_input(table, BLOCK_PREVIEW_LEN));
+            }
+        }
+        Ok(())
+    }
+
+    /// Opens the puzzle page in the default browser, printing the URL if that fails.
+    pub(crate) fn open_puzzle_page(&self) {
+        let url = self.puzzle_url();
+        #[cfg(target_os = "windows")]
+        let mut command = {
+            let mut command = Command::new("cmd");
+            command.args(["/C", "start", ""]);
+            command
+        };
+        #[cfg(target_os = "macos")]
+        let mut command = Command::new("open");
+        #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+        let mut command = Command::new("xdg-open");
+        let opened = command
+            .arg(&url)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+        if opened {
+            println!("Opened {url}");
+        } else {
+            println!("Could not open a browser; the puzzle is at {url}");
+        }
+    }
+
+    pub(crate) fn dump_html(&self, session: &Session, path: &Path) -> Result<()> {
+        let (html, _) = self.get_puzzle_page(session)?;
+        if path == Path::new("-") {
+            print!("{html}");
+        } else {
+            write(path, html).with_context(|| format!("failed to write `{}`", path.display()))?;
+            println!("Saved puzzle page to {}", path.display());
+        }
+        Ok(())
+    }
+
+    /// Includes the title of the puzzle if its page is cached; it is never downloaded just for
+    /// the header.
+    pub(crate) fn print_header(&self) {
+        let title = self
+            .cached_title()
+            .map(|title| format!(": {title}"))
+            .unwrap_or_default();
+        println!(
+            "Advent of Code {} - Day {}{title} - Part {}",
+            self.year,
+            self.day,
+            self.part.number()
+        );
+        println!();
+    }
+
+    fn cached_title(&self) -> Option<String> {
+        let page = read_to_string(self.cache_dir().ok()?.join("puzzle.html")).ok()?;
+        scrape_title(&Html::parse_document(&page))
+    }
+
+    pub(crate) fn get_input_verbose(&self, session: &Session) -> Result<String> {
+        print!("Grabbing input... ");
+        stdout().flush()?;
+        let (input, source) = self.get_input_with_source(session)?;
+        println!("got {} bytes from {source}.", input.len());
+        println!();
+        Ok(input)
+    }
+
+    pub(crate) fn solve(
+        &self,
+        solution: Option<&str>,
+        session: &Session,
+        output_format: OutputFormat,
+        group_digits: bool,
+    ) -> Result<PuzzleResult> {
+        let Solution {
+            name,
+            solve,
+            format,
+            ..
+        } = self.get_solution(solution)?;
+        let result = match output_format {
+            OutputFormat::Text => {
+                self.print_star(session);
+                let input = self.get_input_verbose(session)?;
+                let result = run_solution(solve, &input)?;
+                println!("{}", display_result(format, &result, group_digits));
+                if let Some(letters) = result.read_letters() {
+                    println!("(reads {letters})");
+                }
+                result
+            }
+            _ => {
+                let input = self.get_input(session)?;
+                let result = run_solution(solve, &input)?;
+                self.print_result_record(output_format, name, &result)?;
+                result
+            }
+        };
+        Ok(result)
+    }
+
+    /// Prints the result without any decoration in one of the machine readable formats.
+    fn print_result_record(
+        &self,
+        output_format: OutputFormat,
+        solution: &str,
+        result: &PuzzleResult,
+    ) -> Result<()> {
+        match output_format {
+            OutputFormat::Jsonl => self.print_json_line(solution, result)?,
+            OutputFormat::Line => println!(
+                "{} {} {} {solution} {result}",
+                self.year,
+                self.day,
+                self.part.number()
+            ),
+            OutputFormat::Plain => println!("{result}"),
+            OutputFormat::Text | OutputFormat::Csv => {
+                unreachable!("results are only printed as records in machine readable formats")
+            }
+        }
+        Ok(())
+    }
+
+    /// Posts the result as the answer for this part and reports how Advent of Code responded.
+    pub(crate) fn submit(&self, session: &Session, result: &PuzzleResult) -> Result<()> {
+        println!();
+        if session.dry_network {
+            println!(
+                "{}",
+                Color::Yellow.paint("Skipped submitting, since the network is disabled")
+            );
+            return Ok(());
+        }
+
+        let answer = match result {
+            PuzzleResult::Grid(_) => result
+                .read_letters()
+                .context("could not read the letters of the grid; submit them by hand")?,
+            _ => result.to_string(),
+        };
+        if let Some(accepted) = self.get_accepted_answer(session)? {
+            println!(
+                "{}",
+                Color::Yellow.paint(format!("Already solved (answer: {accepted})"))
+            );
+            if accepted != answer {
+                println!(
+                    "{}",
+                    Color::Yellow.paint(format!(
+                        "The result `{answer}` differs and was not submitted"
+                    ))
+                );
+            }
+            return Ok(());
+        }
+
+        let level = self.part.number().to_string();
+        let response = self.post_with_session(
+            session,
+            &self.answer_url(),
+            &[("level", &level), ("answer", &answer)],
+        )?;
+        let article = Html::parse_document(&response)
+            .select(&Selector::parse("article").unwrap())
+            .next()
+            .map(|article| article.text().collect::<String>())
+            .unwrap_or_default();
+
+        if article.contains("That's the right answer") {
+            println!("{}", Color::Green.paint("★ That's the right answer!"));
+            write_atomically(&self.accepted_answer_path()?, &answer)?;
+            self.clear_cached_calendar_page()?;
+            self.record_answer(&answer)?;
+            println!("Recorded the answer in {ANSWERS_FILE}");
+        } else if article.contains("not the right answer") {
+            let hint = if article.contains("too high") {
+                " (too high)"
+            } else if article.contains("too low") {
+                " (too low)"
+            } else {
+                ""
+            };
+            println!(
+                "{}",
+                Color::Red.paint(format!("✗ That's not the right answer{hint}"))
+            );
+            bail!("wrong answer `{answer}`");
+        } else if article.contains("You gave an answer too recently") {
+            let wait = article
+                .split_once("You have ")
+                .and_then(|(_, rest)| rest.split_once(" left to wait"))
+                .map(|(wait, _)| format!("; wait {wait}"))
+                .unwrap_or_default();
+            println!(
+                "{}",
+                Color::Yellow.paint(format!("You gave an answer too recently{wait}"))
+            );
+        } else if article.contains("You don't seem to be solving the right level") {
+            println!(
+                "{}",
+                Color::Yellow.paint("This part is already solved (or not unlocked yet)")
+            );
+        } else {
+            bail!(
+                "unexpected response when submitting; check {}",
+                self.puzzle_url()
+            );
+        }
+        Ok(())
+    }
+
+    /// Where the answer Advent of Code accepted for this part is recorded.
+    fn accepted_answer_path(&self) -> Result<PathBuf> {
+        Ok(self
+            .cache_dir()?
+            .join(format!("answer_part_{}.txt", self.part.number())))
+    }
+
+    /// Reads the accepted answer from the record or scrapes it from the puzzle page, recording it
+    /// if found, so already solved parts are never submitted again.
+    fn get_accepted_answer(&self, session: &Session) -> Result<Option<String>> {
+        if let Some(answer) = self.read_accepted_answer()? {
+            return Ok(Some(answer));
+        }
+
+        let (page, _) = self.get_puzzle_page(session)?;
+        let answers = scrape_accepted_answers(&Html::parse_document(&page));
+        let Some(answer) = answers.into_iter().nth(usize::from(self.part.number() - 1)) else {
+            return Ok(None);
+        };
+        write_atomically(&self.accepted_answer_path()?, &answer)?;
+        Ok(Some(answer))
+    }
+
+    /// The answer recorded in the answers file or as accepted answer, without any requests.
+    fn known_answer(&self) -> Result<Option<String>> {
+        let answers = load_answers()?;
+        let answer = answers
+            .get(&self.year.into())
+            .and_then(|days| days.get(&self.day.into()))
+            .and_then(|parts| parts.get(&self.part.number()));
+        match answer {
+            Some(answer) => Ok(Some(answer.clone())),
+            None => self.read_accepted_answer(),
+        }
+    }
+
+    fn read_accepted_answer(&self) -> Result<Option<String>> {
+        let path = self.accepted_answer_path()?;
+        match read_to_string(&path) {
+            Ok(answer) => Ok(Some(answer)),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => {
+                Err(error).with_context(|| format!("failed to read `{}`", path.display()))
+            }
+        }
+    }
+
+    /// Prints the answers Advent of Code accepted for both parts, as shown on the puzzle page.
+    pub(crate) fn show_answers(&self, session: &Session) -> Result<()> {
+        let (page, _) = self.get_puzzle_page(session)?;
+        let page = Html::parse_document(&page);
+        let mut scraped = scrape_accepted_answers(&page).into_iter();
+        let unlocked = count_puzzle_parts(&page);
+        for part in [PuzzlePart::Part1, PuzzlePart::Part2] {
+            let puzzle = Puzzle { part, ..*self };
+            let answer = match (scraped.next(), puzzle.read_accepted_answer()?) {
+                (_, Some(recorded)) => recorded,
+                (Some(answer), None) => {
+                    write_atomically(&puzzle.accepted_answer_path()?, &answer)?;
+                    answer
+                }
+                (None, None) if usize::from(part.number()) > unlocked => {
+                    Color::Grey.paint("not unlocked yet")
+                }
+                (None, None) => Color::Grey.paint("not solved yet"),
+            };
+            println!("Part {}: {answer}", part.number());
+        }
+        Ok(())
+    }
+
+    /// Solves both parts using their default solution, downloading the input only once and
+    /// skipping parts that are still to do.
+    pub(crate) fn solve_both(&self, session: &Session, group_digits: bool) -> Result<()> {
+        let mut input = None;
+        for part in [PuzzlePart::Part1, PuzzlePart::Part2] {
+            let puzzle = Puzzle { part, ..*self };
+            puzzle.print_header();
+            if puzzle.is_todo() {
+                println!(
+                    "{}",
+                    Color::Yellow.paint(format!("Part {} is not implemented yet", part.number()))
+                );
+                println!();
+                continue;
+            }
+
+            let Solution { solve, format, .. } = puzzle.get_solution(None)?;
+            puzzle.print_star(session);
+            let input = match &input {
+                Some(input) => input,
+                None => input.insert(puzzle.get_input_verbose(session)?),
+            };
+            let result = run_solution(solve, input)?;
+            println!("{}", display_result(format, &result, group_digits));
+            println!();
+        }
+        Ok(())
+    }
+
+    /// Solves both parts of every implemented day of the year using their default solution and
+    /// prints all results as a table.
+    pub(crate) fn solve_all_for_year(
+        year: u32,
+        session: &Session,
+        output_format: OutputFormat,
+        group_digits: bool,
+    ) -> Result<()> {
+        let text = output_format == OutputFormat::Text;
+        if text {
+            println!("Advent of Code {year}");
+            println!();
+        }
+
+        let mut rows = vec![];
+        for day in 1..=25 {
+            let part1 = Puzzle::new(year, day, PuzzlePart::Part1)?;
+            let part2 = Puzzle {
+                part: PuzzlePart::Part2,
+                ..part1
+            };
+            if part1.get_solutions().is_empty() && part2.get_solutions().is_empty() {
+                continue;
+            }
+
+            let input = if part1.release_time() > Utc::now() {
+                Err("not released yet".to_string())
+            } else {
+                part1
+                    .get_input(session)
+                    .map_err(|error| format!("input unavailable: {error}"))
+            };
+            if !text {
+                match &input {
+                    Ok(input) => {
+                        for puzzle in [part1, part2] {
+                            if let Ok(Solution { name, solve, .. }) = puzzle.get_solution(None) {
+                                match run_solution(solve, input) {
+                                    Ok(result) => {
+                                        puzzle.print_result_record(output_format, name, &result)?
+                                    }
+                                    Err(error) => eprintln!(
+                                        "Skipped day {day} part {}: {error}",
+                                        puzzle.part.number()
+                                    ),
+                                }
+                            }
+                        }
+                    }
+                    Err(error) => eprintln!("Skipped day {day}: {error}"),
+                }
+                continue;
+            }
+
+            let results = [part1, part2].map(|puzzle| {
+                match (&input, puzzle.get_solution(None).ok()) {
+                    (Err(error), _) => error.clone(),
+                    (Ok(_), None) => "-".to_string(),
+                    (Ok(input), Some(Solution { solve, format, .. })) => {
+                        match run_solution(solve, input) {
+                            // Grids span multiple lines, which doesn't fit into a table.
+                            Ok(result @ PuzzleResult::Grid(_)) => result
+                                .read_letters()
+                                .unwrap_or_else(|| "unreadable grid".to_string()),
+                            Ok(result) => display_result(format, &result, group_digits),
+                            Err(error) => error.to_string(),
+                        }
+                    }
+                }
+            });
+            rows.push((day, results));
+        }
+
+        if !text {
+            return Ok(());
+        }
+        if rows.is_empty() {
+            println!("No days of {year} are implemented yet");
+            return Ok(());
+        }
+
+        let width = |index: usize, title: &str| {
+            rows.iter()
+                .map(|(_, results)| results[index].chars().count())
+                .chain(once(title.len()))
+                .max()
+                .unwrap()
+        };
+        let part1_width = width(0, "Part 1");
+        let part2_width = width(1, "Part 2");
+
+        const WS: &str = "";
+
+        ascii_println!("┏━━━━━┳━{WS:━<part1_width$}━┯━{WS:━<part2_width$}━┓");
+        ascii_println!(
+            "┃ Day ┃ {:>part1_width$} │ {:>part2_width$} ┃",
+            "Part 1",
+            "Part 2"
+        );
+        ascii_println!("┣━━━━━╋━{WS:━<part1_width$}━┿━{WS:━<part2_width$}━┫");
+        for (day, [part1, part2]) in &rows {
+            ascii_println!("┃ {day:>3} ┃ {part1:>part1_width$} │ {part2:>part2_width$} ┃");
+        }
+        ascii_println!("┗━━━━━┻━{WS:━<part1_width$}━┷━{WS:━<part2_width$}━┛");
+        Ok(())
+    }
+
+    /// Fails if the result differs from the answer recorded in the answers file.
+    pub(crate) fn verify(
+        &self,
+        result: &PuzzleResult,
+        compare_mode: CompareMode,
+        output_format: OutputFormat,
+    ) -> Result<()> {
+        let answers = load_answers()?;
+        let Some(answer) = answers
+            .get(&self.year.into())
+            .and_then(|days| days.get(&self.day.into()))
+            .and_then(|parts| parts.get(&self.part.number()))
+        else {
+            bail!("no answer recorded in {ANSWERS_FILE}; submit it with --submit first");
+        };
+        if !result.matches(answer, compare_mode) {
+            bail!("result `{result}` does not match the recorded answer `{answer}`");
+        }
+        if output_format == OutputFormat::Text {
+            println!("(matches the recorded answer)");
+        }
+        Ok(())
+    }
+
+    fn record_answer(&self, answer: &str) -> Result<()> {
+        let mut answers = load_answers()?;
+        answers
+            .entry(self.year.into())
+            .or_default()
+            .entry(self.day.into())
+            .or_default()
+            .insert(self.part.number(), answer.to_string());
+        write(ANSWERS_FILE, serde_json::to_string_pretty(&answers)? + "\n")
+            .with_context(|| format!("failed to write `{ANSWERS_FILE}`"))
+    }
+
+    /// Runs the solution on the inputs of two different accounts and reports both results.
+    pub(crate) fn solve_for_sessions(
+        &self,
+        solution: Option<&str>,
+        session_a: &Session,
+        session_b: &str,
+        group_digits: bool,
+    ) -> Result<()> {
+        let Solution { solve, format, .. } = self.get_solution(solution)?;
+
+        let input_a = self.get_input_verbose(session_a)?;
+        let session_b = session_a.with_other_cookie(session_b.to_string());
+        let input_b = self.get_input_verbose(&session_b)?;
+        if input_a == input_b {
+            println!(
+                "{}",
+                Color::Yellow.paint("WARNING: Both sessions got the same input")
+            );
+            println!();
+        }
+
+        let result_a = run_solution(solve, &input_a)?;
+        println!(
+            "Session A: {}",
+            display_result(format, &result_a, group_digits)
+        );
+        let result_b = run_solution(solve, &input_b)?;
+        println!(
+            "Session B: {}",
+            display_result(format, &result_b, group_digits)
+        );
+        Ok(())
+    }
+
+    /// Checks that all solutions agree on generated inputs of every size up to `max_size`.
+    pub(crate) fn fuzz(&self, max_size: usize) -> Result<()> {
+        let solutions = self.get_solutions();
+        if solutions.is_empty() {
+            bail!("puzzle not implemented");
+        }
+        let generate = self
+            .get_generator()
+            .context("puzzle has no synthetic input generator")?;
+
+        print!(
+            "Fuzzing {} solutions with {} generated inputs... ",
+            solutions.len(),
+            max_size.separate_with_commas()
+        );
+        stdout().flush()?;
+        let mut mismatches = 0;
+        for size in 1..=max_size {
+            let input = generate(size);
+            let expected = run_solution(solutions[0].solve, &input)
+                .with_context(|| format!("{} failed on size {size}", solutions[0].name))?;
+            for Solution { name, solve, .. } in &solutions[1..] {
+                let result = run_solution(*solve, &input)
+                    .with_context(|| format!("{name} failed on size {size}"))?;
+                if result != expected {
+                    if mismatches == 0 {
+                        println!();
+                    }
+                    mismatches += 1;
+                    println!(
+                        "{name} disagrees with {} on size {size}: {result} != {expected}",
+                        solutions[0].name
+                    );
+                }
+            }
+        }
+        if mismatches > 0 {
+            bail!("found {mismatches} mismatch(es)");
+        }
+        println!("all agree!");
+        Ok(())
+    }
+
+    /// Runs the named solutions, or all of them, on the input and fails unless they agree with the
+    /// default solution, or the first one if the default is not among them.
+    pub(crate) fn check_solutions(
+        &self,
+        solutions: &[String],
+        session: &Session,
+        compare_mode: CompareMode,
+    ) -> Result<()> {
+        let solutions = self.select_solutions(solutions)?;
+        if solutions.is_empty() {
+            bail!("puzzle not implemented");
+        }
+        let default = self.get_solution(None)?;
+        let reference = solutions
+            .iter()
+            .find(|solution| solution.name == default.name)
+            .unwrap_or(&solutions[0]);
+
+        let input = self.get_input_verbose(session)?;
+
+        let reference_result = run_solution(reference.solve, &input)
+            .with_context(|| format!("{} failed", reference.name))?;
+        let name_width = solutions
+            .iter()
+            .map(|solution| solution.name.len())
+            .max()
+            .unwrap();
+        let mut disagreements = 0;
+        for Solution { name, solve, .. } in &solutions {
+            if *name == reference.name {
+                println!(
+                    "{name:name_width$}  {reference_result} {}",
+                    Color::Grey.paint("(reference)")
+                );
+                continue;
+            }
+            match run_solution(*solve, &input) {
+                Ok(result) if result.matches(&reference_result.to_string(), compare_mode) => {
+                    println!("{name:name_width$}  {result}");
+                }
+                Ok(result) => {
+                    disagreements += 1;
+                    println!(
+                        "{name:name_width$}  {}",
+                        Color::Yellow.paint(format!("{result} != {reference_result}"))
+                    );
+                }
+                Err(error) => {
+                    disagreements += 1;
+                    println!("{name:name_width$}  {}", Color::Red.paint(error));
+                }
+            }
+        }
+        println!();
+
+        if disagreements > 0 {
+            bail!(
+                "{disagreements} solution(s) disagree with {}",
+                reference.name
+            );
+        }
+        println!("All {} solutions agree!", solutions.len());
+        Ok(())
+    }
+
+    /// Records the result as a golden file on the first run and compares against it afterwards.
+    pub(crate) fn check_snapshot(&self, solution: Option<&str>, session: &Session) -> Result<()> {
+        let Solution { name, solve, .. } = self.get_solution(solution)?;
+        let input = self.get_input_verbose(session)?;
+        let result = run_solution(solve, &input)?.to_string();
+
+        let path = Path::new(SNAPSHOT_DIR)
+            .join(self.year.to_string())
+            .join(format!(
+                "day_{}_part_{}_{name}.txt",
+                self.day,
+                self.part.number()
+            ));
+        match read_to_string(&path) {
+            Ok(snapshot) => {
+                let snapshot = snapshot.trim_end();
+                if result != snapshot {
+                    bail!(
+                        "result `{result}` does not match snapshot `{snapshot}` in {}",
+                        path.display()
+                    );
+                }
+                println!("{result} (matches snapshot)");
+            }
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                create_dir_all(path.parent().unwrap())?;
+                write(&path, &result)
+                    .with_context(|| format!("failed to write `{}`", path.display()))?;
+                println!("{result} (recorded snapshot in {})", path.display());
+            }
+            Err(error) => {
+                Err(error).with_context(|| format!("failed to read `{}`", path.display()))?
+            }
+        }
+        Ok(())
+    }
+
+    fn print_json_line(&self, solution: &str, result: &PuzzleResult) -> Result<()> {
+        let mut stdout = stdout().lock();
+        serde_json::to_writer(
+            &mut stdout,
+            &SolveRecord {
+                year: self.year.into(),
+                day: self.day.into(),
+                part: self.part.number(),
+                solution,
+                result,
+            },
+        )?;
+        writeln!(stdout)?;
+        stdout.flush()?;
+        Ok(())
+    }
+
+    pub(crate) fn run_examples(
+        &self,
+        solution: Option<&str>,
+        session: &Session,
+        examples: impl Iterator<Item = Example>,
+        truncate_input_at: usize,
+        compare_mode: CompareMode,
+    ) -> Result<()> {
+        let Solution { solve, .. } = self.get_solution(solution)?;
+        let example_blocks = self.get_example_blocks_verbose(session)?;
+
+        let mut success = 0;
+        let mut total = 0;
+        println!("| Running Examples... ");
+        println!("|---------------------");
+        for example in examples {
+            total += 1;
+            let (input, expected_result) = example.resolve(&example_blocks)?;
+            match run_solution(solve, input) {
+                Ok(result) if example.matches(&result, expected_result, compare_mode) => {
+                    println!("| Example #{total} passed");
+                    success += 1;
+                }
+                Ok(result) => {
+                    println!("| Example #{total} failed: {expected_result} != {result}");
+                    println!("|- Input: {}", truncate_input(input, truncate_input_at));
+                }
+                Err(error) => {
+                    println!("| Example #{total} failed: {error}");
+                    println!("|- Input: {}", truncate_input(input, truncate_input_at));
+                }
+            }
+        }
+        if total > 0 {
+            println!("|---------------------");
+            println!("| {success} / {total} Examples passed");
+        } else {
+            println!("| No Examples found");
+        }
+        Ok(())
+    }
+
+    /// Runs the examples against the named solutions, or all of them if none are named.
+    pub(crate) fn run_examples_for_all_solutions(
+        &self,
+        solutions: &[String],
+        session: &Session,
+        examples: impl Iterator<Item = Example>,
+        compare_mode: CompareMode,
+    ) -> Result<()> {
+        let solutions = self.select_solutions(solutions)?;
+        if solutions.is_empty() {
+            bail!("puzzle not implemented");
+        }
+
+        let example_blocks = self.get_example_blocks_verbose(session)?;
+        let examples = examples
+            .map(|example| {
+                let (input, expected_result) = example.resolve(&example_blocks)?;
+                Ok((example, input, expected_result))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        const SOLUTION: &str = "Solution";
+        let name_width = solutions
+            .iter()
+            .map(|solution| solution.name.len())
+            .chain(once(SOLUTION.len()))
+            .max()
+            .unwrap();
+
+        let mut failures = vec![];
+        println!("| Running Examples for all Solutions... ");
+        println!("|---------------------");
+        print!("| {SOLUTION:<name_width$} |");
+        for index in 0..examples.len() {
+            print!(" {:>3}", format!("#{}", index + 1));
+        }
+        println!();
+        for Solution { name, solve, .. } in &solutions {
+            print!("| {name:<name_width$} |");
+            for (index, (example, input, expected_result)) in examples.iter().enumerate() {
+                let reason = match run_solution(*solve, input) {
+                    Ok(result) if example.matches(&result, expected_result, compare_mode) => {
+                        print!("   ✓");
+                        continue;
+                    }
+                    Ok(result) => format!("{expected_result} != {result}"),
+                    Err(error) => error.to_string(),
+                };
+                print!("   ✗");
+                failures.push((name, index + 1, reason));
+            }
+            println!();
+        }
+        println!("|---------------------");
+        for (name, example, reason) in &failures {
+            println!("| {name} failed Example #{example}: {reason}");
+        }
+        let total = solutions.len() * examples.len();
+        println!("| {} / {total} Checks passed", total - failures.len());
+        Ok(())
+    }
+
+    /// Samples stack traces while repeatedly running the solution and prints the locations where
+    /// most of the time is spent.
+    #[cfg(feature = "profile")]
+    pub(crate) fn print_profile(
+        &self,
+        solution: Option<&str>,
+        session: &Session,
+        duration: Duration,
+    ) -> Result<()> {
+        let Solution { solve, .. } = self.get_solution(solution)?;
+        let input = self.get_input_verbose(session)?;
+
+        ascii_print!("Sampling for {duration:.2?}... ");
+        stdout().flush()?;
+        let guard = pprof::ProfilerGuardBuilder::default()
+            .frequency(PROFILE_FREQUENCY)
+            .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+            .build()?;
+        let start = Instant::now();
+        while start.elapsed() < duration {
+            let _ = black_box(solve(black_box(input.as_str())));
+        }
+        let report = guard.report().build()?;
+        drop(guard);
+        ascii_println!("Done!");
+        println!();
+
+        let mut self_samples = std::collections::HashMap::<String, isize>::new();
+        for (frames, count) in &report.data {
+            // The innermost frames belong to the profiler capturing the stack itself.
+            let leaf = frames.frames.iter().flatten().find(|symbol| {
+                let name = symbol.name();
+                !name.starts_with("backtrace::") && !name.contains("pprof::")
+            });
+            let location = match leaf {
+                Some(symbol) if symbol.filename.is_some() => format!(
+                    "{} ({}:{})",
+                    symbol.name(),
+                    symbol.filename(),
+                    symbol.lineno()
+                ),
+                Some(symbol) => symbol.name(),
+                None => "<unknown>".to_string(),
+            };
+            *self_samples.entry(location).or_default() += count;
+        }
+        let total = self_samples.values().sum::<isize>();
+        if total == 0 {
+            bail!("no samples collected; try sampling for longer");
+        }
+
+        let mut self_samples = self_samples.into_iter().collect::<Vec<_>>();
+        self_samples.sort_by(|(_, lhs), (_, rhs)| rhs.cmp(lhs));
+
+        ascii_println!("  Self% │ Samples │ Location");
+        for (location, count) in self_samples.iter().take(PROFILE_TOP_N) {
+            let percent = *count as f32 / total as f32 * 100.0;
+            ascii_println!("{percent:>6.1}% │ {count:>7} │ {location}");
+        }
+        println!();
+
+        Ok(())
+    }
+
+    /// Gets the real or a synthetic input, only reporting progress and warnings if `verbose`.
+    fn get_benchmark_input(
+        &self,
+        session: &Session,
+        settings: &BenchmarkSettings,
+        verbose: bool,
+    ) -> Result<String> {
+        let Some(size) = settings.synthetic else {
+            if !verbose {
+                return self.get_input(session);
+            }
+            let input = self.get_input_verbose(session)?;
+            warn_small_input(&input, settings);
+            return Ok(input);
+        };
+        let generate = self
+            .get_generator()
+            .context("puzzle has no synthetic input generator")?;
+        if !verbose {
+            return Ok(generate(size));
+        }
+        ascii_print!("Generating synthetic input... ");
+        stdout().flush()?;
+        let input = generate(size);
+        ascii_println!("got {} bytes.", input.len().separate_with_commas());
+        println!();
+        Ok(input)
+    }
+
+    pub(crate) fn print_benchmark(
+        &self,
+        solution: Option<&str>,
+        session: &Session,
+        settings: &BenchmarkSettings,
+        output_format: OutputFormat,
+    ) -> Result<()> {
+        let solution = self.get_solution(solution)?;
+        let Solution {
+            name,
+            solve,
+            format,
+            ..
+        } = solution;
+        let text = output_format == OutputFormat::Text;
+        let input = self.get_benchmark_input(session, settings, text)?;
+        let puzzle_result = run_solution(solve, &input)?;
+
+        let result = self.benchmark(&solution, &input, settings);
+        if !text {
+            return self.print_benchmark_records(
+                output_format,
+                settings.cycles,
+                &[(name, &result)],
+            );
+        }
+        let BenchmarkResult {
+            warmup,
+            warmup_iterations,
+            runtime,
+            overhead,
+            iterations,
+            rejected,
+            extended,
+            average,
+            std_dev,
+            min,
+            med,
+            max,
+            cycles,
+            phases,
+            allocations,
+            ..
+        } = &result;
+
+        if !settings.warmup.is_zero() {
+            ascii_println!(
+                "Warmup ran for {warmup:.2?} ({} iterations)",
+                warmup_iterations.separate_with_commas()
+            );
+        }
+        ascii_println!("Benchmark ran for {runtime:.2?} (plus {overhead:.2?} of overhead)");
+        ascii_println!("  Iterations: {}", iterations.separate_with_commas());
+        if *extended {
+            let min_iterations = settings.min_iterations.unwrap_or_default();
+            if *iterations < min_iterations {
+                ascii_println!(
+                    "              (stopped at the {MIN_ITERATIONS_TIME_CAP:.0?} cap before reaching {})",
+                    min_iterations.separate_with_commas()
+                );
+            } else {
+                ascii_println!(
+                    "              (ran past {:.2?} to reach {})",
+                    settings.duration,
+                    min_iterations.separate_with_commas()
+                );
+            }
+        }
+        if settings.reject_outliers {
+            ascii_println!(
+                "    Rejected: {} (slower than {OUTLIER_FACTOR}× the median)",
+                rejected.separate_with_commas()
+            );
+        }
+        let [average, std_dev, min, med, max] = [average, std_dev, min, med, max].map(|time| {
+            format!(
+                "{}{}",
+                format_time(*time, settings.unit),
+                unit_suffix(settings.unit)
+            )
+        });
+        match settings.trim {
+            Some(trim) => ascii_println!(
+                "  Avg±StdDev: {average} ± {std_dev} (over the middle {}%)",
+                100.0 - 2.0 * trim
+            ),
+            None => ascii_println!("  Avg±StdDev: {average} ± {std_dev}"),
+        }
+        ascii_println!(" Min<Med<Max: {min} < {med} < {max}");
+        if let Some(PhaseStats { parse, solve }) = phases {
+            let [parse, solve] = [parse, solve].map(|time| {
+                format!(
+                    "{}{}",
+                    format_time(*time, settings.unit),
+                    unit_suffix(settings.unit)
+                )
+            });
+            ascii_println!(" Parse+Solve: {parse} + {solve}");
+        }
+        if let Some(CycleStats {
+            average,
+            min,
+            med,
+            max,
+        }) = cycles
+        {
+            ascii_println!("  Avg Cycles: {}", average.separate_with_commas());
+            ascii_println!(
+                " Min<Med<Max: {} < {} < {}",
+                min.separate_with_commas(),
+                med.separate_with_commas(),
+                max.separate_with_commas()
+            );
+        }
+        if let Some(AllocationStats {
+            allocations,
+            total_bytes,
+            peak_bytes,
+        }) = allocations
+        {
+            ascii_println!(
+                " Allocations: {} ({} total, {} peak)",
+                allocations.separate_with_commas(),
+                format_bytes(*total_bytes),
+                format_bytes(*peak_bytes)
+            );
+        }
+        // Known answers are only for the real input.
+        let known_answer = match settings.synthetic {
+            Some(_) => None,
+            None => self.known_answer()?,
+        };
+        let formatted = format(&puzzle_result);
+        match known_answer {
+            Some(answer) if puzzle_result.matches(&answer, settings.compare_mode) => {
+                ascii_println!("      Result: {formatted} (✓ matches accepted)")
+            }
+            Some(answer) => {
+                ascii_println!(
+                    "{}",
+                    Color::Yellow.paint(format!(
+                        "      Result: {formatted} (✗ accepted answer is {answer})"
+                    ))
+                )
+            }
+            None => ascii_println!("      Result: {formatted}"),
+        }
+        println!();
+
+        if let Some(output_dir) = &settings.output_dir {
+            self.save_benchmark_run(output_dir, &[(name, &result)])?;
+        }
+
+        Ok(())
+    }
+
+    /// Benchmarks the solution on synthetic inputs of each size to reveal how its runtime scales.
+    pub(crate) fn print_scaling(
+        &self,
+        solution: Option<&str>,
+        settings: &BenchmarkSettings,
+        sizes: &[usize],
+    ) -> Result<()> {
+        let solution = self.get_solution(solution)?;
+        let Solution { name, solve, .. } = solution;
+        let generate = self
+            .get_generator()
+            .context("puzzle has no synthetic input generator")?;
+
+        let sizes = sizes
+            .iter()
+            .map(|&size| (size, size.separate_with_commas()))
+            .collect::<Vec<_>>();
+        let size_width = sizes
+            .iter()
+            .map(|(_, size)| size.len())
+            .chain(once("Bytes".len()))
+            .max()
+            .unwrap();
+
+        const WS: &str = "";
+
+        ascii_println!("Scaling of {name}:");
+        println!();
+        ascii_println!(
+            "┏━{WS:━>size_width$}━┳{}┯━ Per Byte ┯━ Exponent ┓",
+            time_header("━ Average ±   StdDev ", "Avg ± StdDev", 21, settings.unit)
+        );
+        ascii_println!("┃ {:>size_width$} ┃ {WS:19} │ {WS:9} │ {WS:9} ┃", "Bytes");
+        ascii_println!("┣━{WS:━>size_width$}━╋━━━━━━━━━━━━━━━━━━━━━┿━━━━━━━━━━━┿━━━━━━━━━━━┫");
+
+        let mut previous: Option<(usize, Duration)> = None;
+        for (size, formatted_size) in &sizes {
+            let input = generate(*size);
+            run_solution(solve, &input).with_context(|| format!("failed on size {size}"))?;
+            let BenchmarkResult {
+                average, std_dev, ..
+            } = self.benchmark(&solution, &input, settings);
+            let [formatted_average, std_dev] =
+                [average, std_dev].map(|time| format_time(time, settings.unit));
+            let per_byte = format!(
+                "{:.2}ns",
+                average.as_secs_f64() * 1e9 / input.len().max(1) as f64
+            );
+            // The exponent k of a runtime growing like n^k between this and the previous size.
+            let exponent = match previous {
+                Some((previous_len, previous_average)) if previous_len != input.len() => {
+                    let time_ratio = average.as_secs_f64() / previous_average.as_secs_f64();
+                    let size_ratio = input.len() as f64 / previous_len as f64;
+                    format!("{:>9.2}", time_ratio.ln() / size_ratio.ln())
+                }
+                _ => format!("{WS:>9}"),
+            };
+            ascii_println!(
+                "┃ {formatted_size:>size_width$} ┃ {formatted_average:>8} ± {std_dev:>8} │ {per_byte:>9} │ {exponent} ┃"
+            );
+            stdout().flush()?;
+            previous = Some((input.len(), average));
+        }
+
+        ascii_println!("┗━{WS:━>size_width$}━┻━━━━━━━━━━━━━━━━━━━━━┷━━━━━━━━━━━┷━━━━━━━━━━━┛");
+        ascii_println!("  An exponent of 1 means linear growth, 2 quadratic growth and so on.");
+        if let Some(trim) = settings.trim {
+            ascii_println!(
+                "  Averages and standard deviations are over the middle {}% of iterations",
+                100.0 - 2.0 * trim
+            );
+        }
+        println!();
+        Ok(())
+    }
+
+    /// Compares the named solutions, or all of them if none are named.
+    pub(crate) fn print_benchmark_comparison(
+        &self,
+        solutions: &[String],
+        session: &Session,
+        settings: &BenchmarkSettings,
+        output_format: OutputFormat,
+    ) -> Result<()> {
+        let all_solutions = self.select_solutions(solutions)?;
+        let text = output_format == OutputFormat::Text;
+        let input = self.get_benchmark_input(session, settings, text)?;
+
+        if all_solutions.is_empty() {
+            bail!("puzzle has no solutions");
+        }
+
+        const SOLUTION: &str = "Solution";
+        let name_width = all_solutions
+            .iter()
+            .map(|solution| solution.name.len())
+            .chain(once(SOLUTION.len()))
+            .max()
+            .unwrap();
+
+        if text {
+            ascii_print!("Estimating... ");
+            stdout().flush()?;
+        }
+        // Solutions that fail on the input are reported instead of being benchmarked.
+        let mut solutions = vec![];
+        let mut dry_runs = vec![];
+        let mut failures = vec![];
+        for solution in &all_solutions {
+            let start = Instant::now();
+            match run_solution(solution.solve, &input) {
+                Ok(result) => {
+                    solutions.push(solution);
+                    dry_runs.push((result, start.elapsed()));
+                }
+                Err(error) => failures.push((solution.name, error)),
+            }
+        }
+        if solutions.is_empty() {
+            if text {
+                println!();
+            }
+            for (name, error) in &failures {
+                eprintln!("{name}: {error}");
+            }
+            bail!("all solutions failed");
+        }
+
+        // Allocations of other threads would end up in the counts.
+        let threads = if settings.allocations {
+            1
+        } else {
+            available_parallelism()
+                .map_or(1, NonZeroUsize::get)
+                .min(solutions.len())
+        };
+        let estimate = dry_runs
+            .iter()
+            .map(|(_, time)| settings.estimate_duration(*time))
+            .sum::<Duration>()
+            / threads as u32;
+        if text {
+            ascii_println!("estimated total: ~{estimate:.0?}");
+            println!();
+        }
+
+        if text
+            && estimate >= CONFIRM_ESTIMATE_THRESHOLD
+            && stdin().is_terminal()
+            && stdout().is_terminal()
+        {
+            ascii_print!("Continue? [Y/n] ");
+            stdout().flush()?;
+            let mut answer = String::new();
+            stdin().read_line(&mut answer)?;
+            println!();
+            if answer.trim().eq_ignore_ascii_case("n") {
+                ascii_println!("Aborted.");
+                return Ok(());
+            }
+        }
+
+        if text {
+            ascii_print!("Benchmarking 0/{}...", solutions.len());
+            stdout().flush()?;
+        }
+        // Workers pick the next solution until none are left; the lock keeps progress coherent.
+        let next = AtomicUsize::new(0);
+        let finished = Mutex::new(0);
+        let mut results = scope(|scope| {
+            let workers = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut results = vec![];
+                        while let Some(solution) =
+                            solutions.get(next.fetch_add(1, Ordering::Relaxed))
+                        {
+                            let name = &solution.name;
+                            let result = self.benchmark(solution, &input, settings);
+                            let mut finished = finished.lock().unwrap();
+                            *finished += 1;
+                            if text {
+                                ascii_print!(
+                                    "\r{}Benchmarking {finished}/{} - {name} done",
+                                    clear_line(),
+                                    solutions.len()
+                                );
+                                stdout().flush().unwrap();
+                            }
+                            results.push((*name, result));
+                        }
+                        results
+                    })
+                })
+                .collect::<Vec<_>>();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        if text {
+            ascii_print!("\r{}", clear_line());
+        }
+
+        // Restore the order of the solutions, since the first one is the reference result.
+        results
+            .sort_by_key(|(name, _)| solutions.iter().position(|solution| solution.name == *name));
+        let mut benchmark_results = results
+            .into_iter()
+            .zip(dry_runs)
+            .map(|((name, result), (puzzle_result, _))| (name, puzzle_result, result))
+            .collect::<Vec<_>>();
+
+        let first_puzzle_result = benchmark_results.first().unwrap().1.clone();
+
+        benchmark_results.sort_by_key(|(_, _, result)| result.average);
+
+        if !text {
+            for (name, error) in &failures {
+                eprintln!("{name}: {error}");
+            }
+            let records = benchmark_results
+                .iter()
+                .map(|(name, _, result)| (*name, result))
+                .collect::<Vec<_>>();
+            return self.print_benchmark_records(output_format, settings.cycles, &records);
+        }
+
+        let fastest_time = benchmark_results[0].2.average;
+
+        const WS: &str = "";
+
+        let (cycles_header, cycles_separator, cycles_footer) = if settings.cycles {
+            ("┳━━━━ Cycles ", "╋━━━━━━━━━━━━", "┻━━━━━━━━━━━━")
+        } else {
+            ("", "", "")
+        };
+        let show_phases = benchmark_results
+            .iter()
+            .any(|(_, _, result)| result.phases.is_some());
+        let (phases_header, phases_separator, phases_footer) = if show_phases {
+            (
+                format!(
+                    "┳{}┯{}",
+                    time_header("━━━ Parse ", "Parse", 10, settings.unit),
+                    time_header("━━━ Solve ", "Solve", 10, settings.unit)
+                ),
+                "╋━━━━━━━━━━┿━━━━━━━━━━",
+                "┻━━━━━━━━━━┷━━━━━━━━━━",
+            )
+        } else {
+            (String::new(), "", "")
+        };
+        let (allocations_header, allocations_separator, allocations_footer) =
+            if settings.allocations {
+                (
+                    "┳━━━━ Allocs ┯━━━━━━ Peak ",
+                    "╋━━━━━━━━━━━━┿━━━━━━━━━━━━",
+                    "┻━━━━━━━━━━━━┷━━━━━━━━━━━━",
+                )
+            } else {
+                ("", "", "")
+            };
+
+        let unit = settings.unit;
+        ascii_println!(
+            "  {WS: <name_width$} ┏{}┯ Relative ┳{}┯{}┯{}{cycles_header}{phases_header}{allocations_header}┓",
+            time_header("━━ Averge ±   StdDev ", "Avg ± StdDev", 21, unit),
+            time_header("━ Mininum ", "Min", 10, unit),
+            time_header("━━ Median ", "Med", 10, unit),
+            time_header("━ Maximum ", "Max", 10, unit),
+        );
+        ascii_println!("┏━{WS:━<name_width$}━╋━━━━━━━━━━━━━━━━━━━━━┿━━━━━━━━━━╋━━━━━━━━━━┿━━━━━━━━━━┿━━━━━━━━━━{cycles_separator}{phases_separator}{allocations_separator}┫");
+
+        let mut mismatches = 0;
+        for (
+            name,
+            puzzle_result,
+            BenchmarkResult {
+                average,
+                std_dev,
+                min,
+                med,
+                max,
+                cycles,
+                phases,
+                allocations,
+                ..
+            },
+        ) in &benchmark_results
+        {
+            let wrong = match settings.compare_mode {
+                CompareMode::Exact => puzzle_result != &first_puzzle_result,
+                CompareMode::Value => {
+                    !puzzle_result.value_matches(&first_puzzle_result.to_string())
+                }
+            };
+            mismatches += usize::from(wrong);
+            let rel = (average.as_secs_f32() / fastest_time.as_secs_f32() - 1.0) * 100.0;
+            let [average, std_dev, min, med, max] =
+                [average, std_dev, min, med, max].map(|time| format_time(*time, settings.unit));
+            if wrong {
+                ascii_print!("{}", Color::Grey.code());
+            }
+            ascii_print!("┃ {name:<name_width$} ┃ {average:>8} ± {std_dev:>8} │ {rel:>7.1}% ┃ {min:>8} │ {med:>8} │ {max:>8} ┃");
+            if let Some(cycles) = cycles {
+                ascii_print!(" {:>10} ┃", cycles.average.separate_with_commas());
+            }
+            match phases {
+                Some(PhaseStats { parse, solve }) => ascii_print!(
+                    " {:>8} │ {:>8} ┃",
+                    format_time(*parse, settings.unit),
+                    format_time(*solve, settings.unit)
+                ),
+                None if show_phases => ascii_print!(" {WS:>8} │ {WS:>8} ┃"),
+                None => {}
+            }
+            if let Some(allocations) = allocations {
+                ascii_print!(
+                    " {:>10} │ {:>10} ┃",
+                    allocations.allocations.separate_with_commas(),
+                    format_bytes(allocations.peak_bytes)
+                );
+            }
+            if wrong {
+                ascii_print!(
+                    " {}",
+                    Color::Yellow.paint(format!("{puzzle_result} != {first_puzzle_result}"))
+                );
+            }
+            println!();
+        }
+
+        ascii_println!("┗━{WS:━<name_width$}━┻━━━━━━━━━━━━━━━━━━━━━┷━━━━━━━━━━┻━━━━━━━━━━┷━━━━━━━━━━┷━━━━━━━━━━{cycles_footer}{phases_footer}{allocations_footer}┛");
+        print_comparison_summary(&benchmark_results, mismatches);
+
+        for (name, error) in &failures {
+            ascii_println!("  {}", Color::Red.paint(format!("{name}: {error}")));
+        }
+
+        let extended = benchmark_results
+            .iter()
+            .filter(|(_, _, result)| result.extended)
+            .map(|(name, ..)| *name)
+            .collect::<Vec<_>>();
+        if !extended.is_empty() {
+            ascii_println!(
+                "  Ran past {:.2?} to reach {} iterations: {}",
+                settings.duration,
+                settings
+                    .min_iterations
+                    .unwrap_or_default()
+                    .separate_with_commas(),
+                extended.join(", ")
+            );
+        }
+
+        if !settings.warmup.is_zero() {
+            ascii_println!(
+                "  Warmed up each solution for {:.2?} before measuring {}",
+                settings.warmup,
+                settings.describe_measuring()
+            );
+        }
+
+        if settings.significance {
+            if let [(fastest_name, _, fastest), (second_name, _, second), ..] =
+                benchmark_results.as_slice()
+            {
+                let seconds = |result: &BenchmarkResult| {
+                    result
+                        .times
+                        .iter()
+                        .map(Duration::as_secs_f64)
+                        .collect::<Vec<_>>()
+                };
+                match welch_t_test(&seconds(fastest), &seconds(second)) {
+                    Some(p) if p < SIGNIFICANCE_LEVEL => ascii_println!(
+                        "  {fastest_name} is faster than {second_name} (p = {p:.4}, significant)"
+                    ),
+                    Some(p) => ascii_println!(
+                        "  {fastest_name} vs {second_name}: difference not significant (p = {p:.4})"
+                    ),
+                    None => ascii_println!(
+                        "  {fastest_name} vs {second_name}: not enough iterations for a significance test"
+                    ),
+                }
+            }
+        }
+
+        if let Some(trim) = settings.trim {
+            ascii_println!(
+                "  Averages and standard deviations are over the middle {}% of iterations",
+                100.0 - 2.0 * trim
+            );
+        }
+
+        if settings.reject_outliers {
+            let rejected = benchmark_results
+                .iter()
+                .map(|(_, _, result)| result.rejected)
+                .sum::<usize>();
+            ascii_println!(
+                "  Rejected {} outliers (slower than {OUTLIER_FACTOR}× the median)",
+                rejected.separate_with_commas()
+            );
+        }
+
+        for (name, ..) in &benchmark_results {
+            let description = solutions
+                .iter()
+                .find(|solution| solution.name == *name)
+                .and_then(|solution| solution.description);
+            if let Some(description) = description {
+                ascii_println!("  {name:<name_width$}   {description}");
+            }
+        }
+
+        if let Some(output_dir) = &settings.output_dir {
+            let results = benchmark_results
+                .iter()
+                .map(|(name, _, result)| (*name, result))
+                .collect::<Vec<_>>();
+            println!();
+            self.save_benchmark_run(output_dir, &results)?;
+        }
+
+        Ok(())
+    }
+
+    /// Prints one JSON object per solution for `jsonl` or a table with a header row for `csv`, with
+    /// all durations in nanoseconds.
+    fn print_benchmark_records(
+        &self,
+        output_format: OutputFormat,
+        cycles: bool,
+        results: &[(&str, &BenchmarkResult)],
+    ) -> Result<()> {
+        let mut stdout = stdout().lock();
+        match output_format {
+            OutputFormat::Jsonl => {
+                for (solution, result) in results {
+                    serde_json::to_writer(
+                        &mut stdout,
+                        &BenchmarkLine {
+                            year: self.year.into(),
+                            day: self.day.into(),
+                            part: self.part.number(),
+                            record: result.to_record(solution),
+                        },
+                    )?;
+                    writeln!(stdout)?;
+                }
+            }
+            OutputFormat::Csv => {
+                write!(stdout, "year,day,part,solution,iterations,rejected,runtime_ns,overhead_ns,average_ns,std_dev_ns,min_ns,med_ns,max_ns")?;
+                if cycles {
+                    write!(stdout, ",average_cycles,min_cycles,med_cycles,max_cycles")?;
+                }
+                writeln!(stdout)?;
+                for (solution, result) in results {
+                    write!(
+                        stdout,
+                        "{},{},{},{solution},{},{},{},{},{},{},{},{},{}",
+                        self.year,
+                        self.day,
+                        self.part.number(),
+                        result.iterations,
+                        result.rejected,
+                        result.runtime.as_nanos(),
+                        result.overhead.as_nanos(),
+                        result.average.as_nanos(),
+                        result.std_dev.as_nanos(),
+                        result.min.as_nanos(),
+                        result.med.as_nanos(),
+                        result.max.as_nanos(),
+                    )?;
+                    if let Some(CycleStats {
+                        average,
+                        min,
+                        med,
+                        max,
+                    }) = &result.cycles
+                    {
+                        write!(stdout, ",{average},{min},{med},{max}")?;
+                    }
+                    writeln!(stdout)?;
+                }
+            }
+            OutputFormat::Text | OutputFormat::Line | OutputFormat::Plain => {
+                unreachable!("benchmarks are printed as text and don't support single lines")
+            }
+        }
+        stdout.flush()?;
+        Ok(())
+    }
+
+    /// Writes the results to a new JSON file named after the current time and the puzzle.
+    fn save_benchmark_run(
+        &self,
+        output_dir: &Path,
+        results: &[(&str, &BenchmarkResult)],
+    ) -> Result<()> {
+        let now = Utc::now();
+        let path = output_dir.join(format!(
+            "{}_{}_day_{}_part_{}.json",
+            now.format("%Y-%m-%dT%H-%M-%S%.3fZ"),
+            self.year,
+            self.day,
+            self.part.number()
+        ));
+
+        create_dir_all(output_dir)?;
+        let file = File::create(&path)
+            .with_context(|| format!("failed to create `{}`", path.display()))?;
+        serde_json::to_writer_pretty(
+            BufWriter::new(file),
+            &BenchmarkRun {
+                year: self.year.into(),
+                day: self.day.into(),
+                part: self.part.number(),
+                timestamp: now.to_rfc3339_opts(SecondsFormat::Millis, true),
+                results: results
+                    .iter()
+                    .map(|(solution, result)| result.to_record(solution))
+                    .collect(),
+            },
+        )?;
+        println!("Saved benchmark results to {}", path.display());
+
+        Ok(())
+    }
+
+    fn benchmark(
+        &self,
+        solution: &Solution,
+        input: &str,
+        settings: &BenchmarkSettings,
+    ) -> BenchmarkResult {
+        let solve = solution.solve;
+        // Measured before anything else, while the benchmark itself doesn't allocate yet.
+        let allocations = settings
+            .allocations
+            .then(|| count_allocations(|| black_box(solve(black_box(input)))).1);
+
+        // Using Vec and then sort to minimize overhead compared to e.g. BTreeSet.
+        // Pre-allocating some capacity doesn't make much difference and picking a good initial
         // capacity isn't really possible without running the benchmark upfront.
         let mut times = vec![];
+        let mut cycles = vec![];
+        let mut phase_times = vec![];
+        let mut median_estimate = None;
+        let mut last_estimate_len = 0;
+        let mut rejected = 0;
+        let mut extended = false;
+
+        let warmup_start = Instant::now();
+        let mut warmup_iterations = 0;
+        while warmup_start.elapsed() < settings.warmup {
+            let _ = black_box(solve(black_box(input)));
+            warmup_iterations += 1;
+        }
+        let warmup = warmup_start.elapsed();
+
         let start = Instant::now();
         loop {
             let iteration_start = Instant::now();
-            black_box(solve(black_box(input)));
-            times.push(iteration_start.elapsed());
+            let cycles_start = settings.cycles.then(read_cycle_counter);
+            let iteration_phase_times = match solution.phases {
+                Some(phases) => Some(phases(black_box(input))),
+                None => {
+                    let _ = black_box(solve(black_box(input)));
+                    None
+                }
+            };
+            let iteration_cycles = cycles_start.map(|start| read_cycle_counter() - start);
+            let time = iteration_start.elapsed();
+
+            if settings.reject_outliers {
+                // Sorting on every iteration would be way too slow, so the median is only
+                // re-estimated whenever the number of samples doubles.
+                // Rejected samples don't grow `times`, so the length is remembered to not
+                // re-estimate on each of them.
+                if times.len().is_power_of_two() && times.len() != last_estimate_len {
+                    let mut sorted = times.clone();
+                    median_estimate = Some(*sorted.select_nth_unstable(times.len() / 2).1);
+                    last_estimate_len = times.len();
+                }
+            }
+
+            if median_estimate.is_some_and(|median| time > median * OUTLIER_FACTOR) {
+                rejected += 1;
+            } else {
+                times.push(time);
+                cycles.extend(iteration_cycles);
+                phase_times.extend(iteration_phase_times);
+            }
+
+            let elapsed = start.elapsed();
+            if settings.is_done(elapsed, times.len()) {
+                break;
+            }
+            extended |= elapsed >= settings.duration;
+        }
+        let elapsed_with_overhead = start.elapsed();
+        let mut runtime = times.iter().sum::<Duration>();
+        let mut overhead = elapsed_with_overhead - runtime;
+
+        if let Some(factor) = settings.normalization {
+            for time in &mut times {
+                *time = time.mul_f64(factor);
+            }
+            for (parse, solve) in &mut phase_times {
+                *parse = parse.mul_f64(factor);
+                *solve = solve.mul_f64(factor);
+            }
+            runtime = runtime.mul_f64(factor);
+            overhead = overhead.mul_f64(factor);
+        }
+
+        times.sort_unstable();
+
+        let iterations = times.len();
+        // Always keeps at least one iteration, even for absurd percentages.
+        let trimmed = settings.trim.map_or(0, |trim| {
+            ((iterations as f64 * trim / 100.0) as usize).min(iterations.saturating_sub(1) / 2)
+        });
+        let kept = &times[trimmed..iterations - trimmed];
+        let average = if trimmed == 0 {
+            runtime.div_f32(iterations as f32)
+        } else {
+            kept.iter().sum::<Duration>().div_f32(kept.len() as f32)
+        };
+        let std_dev = if kept.len() > 1 {
+            Duration::from_secs_f32(
+                kept.iter()
+                    .map(|time| (time.as_secs_f32() - average.as_secs_f32()).powi(2))
+                    .sum::<f32>()
+                    .sqrt()
+                    / (kept.len() as f32 - 1.0),
+            )
+        } else {
+            Duration::ZERO
+        };
+
+        BenchmarkResult {
+            warmup,
+            warmup_iterations,
+            runtime,
+            overhead,
+            iterations,
+            rejected,
+            extended,
+            average,
+            std_dev,
+            min: *times.first().unwrap(),
+            med: if iterations % 2 == 0 {
+                (times[iterations / 2 - 1] + times[iterations / 2]) / 2
+            } else {
+                times[iterations / 2]
+            },
+            max: *times.last().unwrap(),
+            cycles: settings.cycles.then(|| {
+                cycles.sort_unstable();
+                CycleStats {
+                    average: cycles.iter().sum::<u64>() / cycles.len() as u64,
+                    min: *cycles.first().unwrap(),
+                    med: cycles[cycles.len() / 2],
+                    max: *cycles.last().unwrap(),
+                }
+            }),
+            phases: (!phase_times.is_empty()).then(|| {
+                let (parse, solve) = phase_times.iter().fold(
+                    (Duration::ZERO, Duration::ZERO),
+                    |(parse_sum, solve_sum), (parse, solve)| {
+                        (parse_sum + *parse, solve_sum + *solve)
+                    },
+                );
+                PhaseStats {
+                    parse: parse.div_f64(phase_times.len() as f64),
+                    solve: solve.div_f64(phase_times.len() as f64),
+                }
+            }),
+            allocations,
+            times,
+        }
+    }
+
+    /// A part is still to do if it has no solutions or its source contains a `todo!()`.
+    fn is_todo(self) -> bool {
+        if self.get_solutions().is_empty() {
+            return true;
+        }
+        self.get_part_source().contains("todo!(")
+    }
+
+    /// The source of the day, starting at this part and, for part 1, ending before part 2.
+    fn get_part_source(self) -> &'static str {
+        let source = self.get_source().unwrap_or_default();
+        match source.find("impl Part<2>") {
+            Some(index) => match self.part {
+                PuzzlePart::Part1 => &source[..index],
+                PuzzlePart::Part2 => &source[index..],
+            },
+            None => match self.part {
+                PuzzlePart::Part1 => source,
+                PuzzlePart::Part2 => "",
+            },
+        }
+    }
+
+    /// Lists the names of the solutions, along with their description if any, for this part or
+    /// both parts, marking the one that runs by default with a `*`.
+    pub(crate) fn list_solutions(self, both_parts: bool) -> Result<()> {
+        let parts = if both_parts {
+            vec![PuzzlePart::Part1, PuzzlePart::Part2]
+        } else {
+            vec![self.part]
+        };
+        let mut solutions = vec![];
+        for part in parts {
+            solutions.push((part, Puzzle { part, ..self }.get_solutions()));
+        }
+        if solutions.iter().all(|(_, solutions)| solutions.is_empty()) {
+            bail!("puzzle not implemented");
+        }
+
+        let width = solutions
+            .iter()
+            .flat_map(|(_, solutions)| solutions.iter())
+            .map(|solution| solution.name.len())
+            .max()
+            .unwrap_or_default();
+        for (part, solutions) in solutions {
+            println!("Part {}:", part.number());
+            if solutions.is_empty() {
+                println!("  {}", Color::Grey.paint("not implemented"));
+            }
+            let default = Puzzle { part, ..self }.get_solution(None).ok();
+            for solution in solutions {
+                let Solution {
+                    name, description, ..
+                } = solution;
+                let marker = if default.is_some_and(|default| default.name == *name) {
+                    "*"
+                } else {
+                    " "
+                };
+                match description {
+                    Some(description) => {
+                        println!("{marker} {name:width$}  {}", Color::Grey.paint(description))
+                    }
+                    None => println!("{marker} {name}"),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Prints the file and line each solution of this part is declared at.
+    pub(crate) fn print_locations(self) -> Result<()> {
+        let solutions = self.get_solutions();
+        if solutions.is_empty() {
+            bail!("no solutions for this puzzle part");
+        }
+
+        let path = format!("src/year_{}/day_{}.rs", self.year, self.day);
+        let source = self.get_source().unwrap_or_default();
+        let part_source = self.get_part_source();
+        let part_start = match self.part {
+            PuzzlePart::Part1 => 0,
+            PuzzlePart::Part2 => source.len() - part_source.len(),
+        };
+        let width = solutions
+            .iter()
+            .map(|solution| solution.name.len())
+            .max()
+            .unwrap_or_default();
+        for solution in solutions {
+            let line = part_source
+                .find(&format!("Solution::new({:?}", solution.name))
+                .map(|index| source[..part_start + index].matches('\n').count() + 1);
+            let name = solution.name;
+            match line {
+                Some(line) => println!("{name:width$} -> {path}:{line}"),
+                None => println!("{name:width$} -> {path}"),
+            }
+        }
+        Ok(())
+    }
 
-            if start.elapsed() >= bench_duration {
-                break;
+    fn get_solution(&self, solution: Option<&str>) -> Result<Solution> {
+        let solutions = self.get_solutions();
+        if let Some(solution) = solution {
+            let find = |solution: &str| {
+                solutions
+                    .iter()
+                    .find(|Solution { name, .. }| *name == solution)
+                    .copied()
+            };
+            if let Some(solution) = find(solution) {
+                return Ok(solution);
             }
+            if let Some(index) = solution.strip_prefix('#') {
+                let index = index
+                    .parse::<usize>()
+                    .with_context(|| format!("invalid solution index `{solution}`"))?;
+                return index
+                    .checked_sub(1)
+                    .and_then(|index| solutions.get(index))
+                    .copied()
+                    .with_context(|| format!("puzzle only has {} solution(s)", solutions.len()));
+            }
+            resolve_alias(solution).and_then(find).with_context(|| {
+                let names = solutions.iter().map(|solution| solution.name);
+                format!(
+                    "solution `{solution}` not found; available solutions: {}",
+                    names.collect::<Vec<_>>().join(", ")
+                )
+            })
+        } else {
+            default_solution(solutions).context("puzzle not implemented")
         }
-        let elapsed_with_overhead = start.elapsed();
-        let runtime = times.iter().sum::<Duration>();
-        let overhead = elapsed_with_overhead - runtime;
+    }
 
-        times.sort_unstable();
+    /// Resolves each name like `--solution`, or returns all solutions if there are none.
+    fn select_solutions(&self, names: &[String]) -> Result<Vec<Solution>> {
+        if names.is_empty() {
+            return Ok(self.get_solutions().to_vec());
+        }
+        names
+            .iter()
+            .map(|name| self.get_solution(Some(name)))
+            .collect()
+    }
 
-        let iterations = times.len();
-        let average = runtime.div_f32(iterations as f32);
-        let std_dev = if iterations > 1 {
-            Duration::from_secs_f32(
-                times
-                    .iter()
-                    .map(|time| (time.as_secs_f32() - average.as_secs_f32()).powi(2))
-                    .sum::<f32>()
-                    .sqrt()
-                    / (iterations as f32 - 1.0),
-            )
+    /// Runs the named solution on the given input without any network access or output.
+    ///
+    /// ```
+    /// # use crate::puzzle::{Puzzle, PuzzlePart, PuzzleResult};
+    /// # fn main() -> anyhow::Result<()> {
+    /// let puzzle = Puzzle::new(2015, 1, PuzzlePart::Part1)?;
+    /// assert_eq!(puzzle.run_named("count", "(()")?, PuzzleResult::Int(1));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[allow(dead_code)]
+    pub(crate) fn run_named(&self, name: &str, input: &str) -> Result<PuzzleResult> {
+        let Solution { solve, .. } = self.get_solution(Some(name))?;
+        solve(input)
+    }
+}
+
+impl BenchmarkResult {
+    fn to_record<'a>(&'a self, solution: &'a str) -> BenchmarkRecord<'a> {
+        BenchmarkRecord {
+            solution,
+            iterations: self.iterations,
+            rejected: self.rejected,
+            runtime_ns: self.runtime.as_nanos(),
+            overhead_ns: self.overhead.as_nanos(),
+            average_ns: self.average.as_nanos(),
+            std_dev_ns: self.std_dev.as_nanos(),
+            min_ns: self.min.as_nanos(),
+            med_ns: self.med.as_nanos(),
+            max_ns: self.max.as_nanos(),
+            cycles: self.cycles.as_ref(),
+        }
+    }
+}
+
+impl Example {
+    pub(crate) const fn new(input: usize, expected_result: usize) -> Self {
+        Self {
+            input,
+            expected_result: ExpectedResult::CodeBlock(expected_result),
+            base: None,
+            table_input: false,
+        }
+    }
+
+    /// Expects the given result instead of taking it from a code block.
+    #[allow(dead_code)]
+    pub(crate) const fn literal(input: usize, expected_result: &'static str) -> Self {
+        Self {
+            input,
+            expected_result: ExpectedResult::Literal(expected_result),
+            base: None,
+            table_input: false,
+        }
+    }
+
+    /// Compares the result as an integer written in the given base, which must be in `2..=36`.
+    #[allow(dead_code)]
+    pub(crate) const fn with_base(self, base: u32) -> Self {
+        assert!(matches!(base, 2..=36), "base must be in 2..=36");
+        Self {
+            base: Some(base),
+            ..self
+        }
+    }
+
+    /// Takes the input from the table at the input index instead of a code block.
+    #[allow(dead_code)]
+    pub(crate) const fn with_table_input(self) -> Self {
+        Self {
+            table_input: true,
+            ..self
+        }
+    }
+
+    fn resolve(self, example_blocks: &ExampleBlocks) -> Result<(&str, &str)> {
+        let input = if self.table_input {
+            example_blocks
+                .tables
+                .get(self.input)
+                .context("example table offset out of bounds")?
         } else {
-            Duration::ZERO
+            example_blocks
+                .code_blocks
+                .get(self.input)
+                .context("example offset out of bounds")?
         };
+        let expected_result = match self.expected_result {
+            ExpectedResult::CodeBlock(index) => example_blocks
+                .code_blocks
+                .get(index)
+                .context("expected result offset out of bounds")?,
+            ExpectedResult::Literal(expected_result) => expected_result,
+        };
+        Ok((input, expected_result))
+    }
 
-        BenchmarkResult {
-            runtime,
-            overhead,
-            iterations,
-            average,
-            std_dev,
-            min: *times.first().unwrap(),
-            med: if iterations % 2 == 0 {
-                (times[iterations / 2 - 1] + times[iterations / 2]) / 2
-            } else {
-                times[iterations / 2]
-            },
-            max: *times.last().unwrap(),
+    /// Compares the result using the compare mode, or the numeric value if a base is given.
+    fn matches(
+        self,
+        result: &PuzzleResult,
+        expected_result: &str,
+        compare_mode: CompareMode,
+    ) -> bool {
+        match (self.base, result.as_integer()) {
+            (Some(base), Some(result)) => parse_int_in_base(expected_result, base) == Some(result),
+            _ => result.matches(expected_result, compare_mode),
         }
     }
+}
 
-    fn get_solution(&self, solution: Option<&str>) -> Result<Solution> {
-        let solutions = self.get_solutions();
-        if let Some(solution) = solution {
-            solutions
-                .iter()
-                .find(|Solution(name, _)| *name == solution)
-                .copied()
-                .context("solution not found")
-        } else {
-            solutions.first().copied().context("puzzle not implemented")
+impl PuzzleResult {
+    /// Shorthand for solutions that can't fail, e.g. `PuzzleResult::ok(floor)`.
+    pub(crate) fn ok(result: impl Into<Self>) -> Result<Self> {
+        Ok(result.into())
+    }
+
+    /// Compares the result with an expected result using the compare mode.
+    pub(crate) fn matches(&self, expected_result: &str, compare_mode: CompareMode) -> bool {
+        match compare_mode {
+            // Multi-line code blocks usually end with a newline that is not part of the result.
+            CompareMode::Exact => {
+                self.to_string().trim_end_matches('\n') == expected_result.trim_end_matches('\n')
+            }
+            CompareMode::Value => self.value_matches(expected_result),
+        }
+    }
+
+    /// Reads the letters of a [`PuzzleResult::Grid`] drawn in the 4×6 pixel font Advent of Code
+    /// uses, with one empty column between letters. Returns [`None`] for any other result or if a
+    /// letter is not recognized.
+    pub(crate) fn read_letters(&self) -> Option<String> {
+        let PuzzleResult::Grid(lines) = self else {
+            return None;
+        };
+        if lines.len() != LETTER_HEIGHT {
+            return None;
+        }
+        let width = lines.iter().map(|line| line.chars().count()).max()?;
+        let pixels = lines
+            .iter()
+            .map(|line| line.chars().map(|pixel| pixel == '#').collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        (0..width)
+            .step_by(LETTER_WIDTH + 1)
+            .map(|left| {
+                let letter = pixels.iter().map(|row| {
+                    (left..left + LETTER_WIDTH)
+                        .map(|x| if row.get(x) == Some(&true) { '#' } else { '.' })
+                        .collect::<String>()
+                });
+                LETTERS
+                    .iter()
+                    .find(|(_, glyph)| letter.clone().eq(glyph.iter().copied()))
+                    .map(|(char, _)| *char)
+            })
+            .collect()
+    }
+
+    /// Compares the value, ignoring surrounding whitespace as well as leading zeros and signs of
+    /// integers.
+    fn value_matches(&self, expected_result: &str) -> bool {
+        match self.as_integer() {
+            Some(result) => parse_int_in_base(expected_result, 10) == Some(result),
+            None => self.to_string().trim() == expected_result.trim(),
+        }
+    }
+
+    /// The value of any of the integer variants, wide enough to hold all of them.
+    fn as_integer(&self) -> Option<i128> {
+        match self {
+            PuzzleResult::Int(result) => Some((*result).into()),
+            PuzzleResult::Int64(result) => Some((*result).into()),
+            PuzzleResult::UInt64(result) => Some((*result).into()),
+            PuzzleResult::Str(_) | PuzzleResult::Grid(_) => None,
+        }
+    }
+}
+
+/// Benchmarks day 1 of 2015 on a generated input and stores how this machine compares to the
+/// baseline.
+pub(crate) fn calibrate() -> Result<()> {
+    let reference = Puzzle::new(2015, 1, PuzzlePart::Part1)?;
+    let solution = reference
+        .get_solutions()
+        .iter()
+        .find(|solution| solution.name == "count")
+        .context("reference solution 2015/1 `count` not found")?;
+    let generate = reference
+        .get_generator()
+        .context("reference puzzle 2015/1 has no input generator")?;
+    let input = generate(CALIBRATION_INPUT_SIZE);
+
+    ascii_print!("Benchmarking reference workload for {CALIBRATION_DURATION:?}... ");
+    stdout().flush()?;
+    let result = reference.benchmark(
+        solution,
+        &input,
+        &BenchmarkSettings {
+            duration: CALIBRATION_DURATION,
+            ..Default::default()
+        },
+    );
+    ascii_println!("took {:.2?}", result.med);
+
+    let calibration = Calibration {
+        factor: CALIBRATION_BASELINE.as_secs_f64() / result.med.as_secs_f64(),
+        reference_ns: result.med.as_nanos(),
+    };
+    let path = calibration_path()?;
+    create_dir_all(path.parent().unwrap())?;
+    let file =
+        File::create(&path).with_context(|| format!("failed to create `{}`", path.display()))?;
+    serde_json::to_writer_pretty(BufWriter::new(file), &calibration)?;
+    ascii_println!(
+        "Saved calibration factor {:.3} to {}",
+        calibration.factor,
+        path.display()
+    );
+    Ok(())
+}
+
+/// Loads the factor stored by [`calibrate`].
+pub(crate) fn load_calibration_factor() -> Result<f64> {
+    let path = calibration_path()?;
+    let calibration = match read_to_string(&path) {
+        Ok(calibration) => calibration,
+        Err(error) if error.kind() == ErrorKind::NotFound => {
+            bail!("machine is not calibrated yet; run with --calibrate first")
+        }
+        Err(error) => {
+            return Err(error).with_context(|| format!("failed to read `{}`", path.display()))
+        }
+    };
+    Ok(serde_json::from_str::<Calibration>(&calibration)
+        .with_context(|| format!("invalid calibration in `{}`", path.display()))?
+        .factor)
+}
+
+/// The root of the cache, which can be changed using the `ADVENT_OF_CODE_CACHE_DIR` env var.
+pub(crate) fn cache_dir() -> Result<PathBuf> {
+    match std::env::var_os(ADVENT_OF_CODE_CACHE_DIR) {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => Ok(dirs::cache_dir()
+            .context("no cache directory available")?
+            .join("advent-of-code-rs")),
+    }
+}
+
+/// The number of parts shown on the puzzle page; part 2 only appears once part 1 is solved.
+fn count_puzzle_parts(page: &Html) -> usize {
+    page.select(&Selector::parse("article.day-desc").unwrap())
+        .count()
+}
+
+/// The solution marked with [`Solution::as_default`], or else the first one.
+fn default_solution(solutions: &[Solution]) -> Option<Solution> {
+    solutions
+        .iter()
+        .find(|solution| solution.default)
+        .or(solutions.first())
+        .copied()
+}
+
+/// The title from the `--- Day N: Title ---` heading of part 1.
+fn scrape_title(page: &Html) -> Option<String> {
+    let heading = page
+        .select(&Selector::parse("article.day-desc h2").unwrap())
+        .next()?
+        .text()
+        .collect::<String>();
+    let title = heading
+        .trim()
+        .trim_start_matches('-')
+        .trim_end_matches('-')
+        .trim()
+        .split_once(": ")?
+        .1
+        .trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+/// The text of every `<code>` element, including text nested in e.g. `<em>` and across lines.
+fn scrape_code_blocks(page: &Html) -> Vec<String> {
+    page.select(&Selector::parse("code").unwrap())
+        .map(|element| element.text().collect())
+        .collect()
+}
+
+/// The answers shown as "Your puzzle answer was ..." below each solved part of the puzzle page.
+fn scrape_accepted_answers(page: &Html) -> Vec<String> {
+    let code_selector = Selector::parse("code").unwrap();
+    page.select(&Selector::parse("main > p").unwrap())
+        .filter(|paragraph| {
+            paragraph
+                .text()
+                .next()
+                .is_some_and(|text| text.starts_with("Your puzzle answer was"))
+        })
+        .filter_map(|paragraph| {
+            paragraph
+                .select(&code_selector)
+                .next()
+                .map(|code| code.text().collect())
+        })
+        .collect()
+}
+
+thread_local! {
+    /// Where the solution running on this thread panicked, if [`run_solution`] is catching it.
+    static CAUGHT_PANIC_LOCATION: RefCell<Option<Option<String>>> = const { RefCell::new(None) };
+}
+
+/// Keeps panics of solutions caught by [`run_solution`] from printing to stderr, unless `debug`.
+pub(crate) fn set_panic_hook(debug: bool) {
+    if debug {
+        return;
+    }
+    let default_hook = take_hook();
+    set_hook(Box::new(move |info| {
+        let caught = CAUGHT_PANIC_LOCATION.with_borrow_mut(|location| {
+            let caught = location.is_some();
+            if caught {
+                *location = Some(info.location().map(ToString::to_string));
+            }
+            caught
+        });
+        if !caught {
+            default_hook(info);
+        }
+    }));
+}
+
+/// Runs the solution, also turning a panic, e.g. from a `todo!()`, into an error.
+fn run_solution(solve: SolutionFn, input: &str) -> Result<PuzzleResult> {
+    CAUGHT_PANIC_LOCATION.set(Some(None));
+    let result = catch_unwind(|| solve(input));
+    let location = CAUGHT_PANIC_LOCATION.take().flatten();
+    result.map_err(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown panic");
+        match location {
+            Some(location) => anyhow!("solution panicked at {location}: {message}"),
+            None => anyhow!("solution panicked: {message}"),
+        }
+    })?
+}
+
+/// How the input endpoint responds before the puzzle is unlocked.
+const NOT_UNLOCKED_RESPONSE: &str = "Please don't repeatedly request this endpoint";
+/// Wait before asking for the input again if it was not unlocked right after the countdown.
+const UNLOCK_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Fails with an actionable message for responses that are an error rather than the input, which
+/// must never be solved or end up in the cache.
+fn check_input_response(response: &str) -> Result<()> {
+    if response.starts_with("Puzzle inputs differ by user") {
+        bail!("not logged in; the session is invalid or expired, refresh {ADVENT_OF_CODE_SESSION}");
+    }
+    if response.starts_with(NOT_UNLOCKED_RESPONSE) {
+        bail!("the puzzle is not unlocked yet");
+    }
+    if response.starts_with("<!DOCTYPE") || response.starts_with("<html") {
+        bail!("got an error page instead of the input; the session is probably expired, refresh {ADVENT_OF_CODE_SESSION}");
+    }
+    if response.is_empty() {
+        bail!("got an empty input");
+    }
+    Ok(())
+}
+
+/// Writes to a temporary file first, so an interrupted run never leaves a partial file behind.
+///
+/// The temporary file is unique to the process and call, so concurrent runs writing the same file
+/// don't clobber each other's temporary file.
+fn write_atomically(path: &Path, contents: &str) -> Result<()> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    create_dir_all(path.parent().unwrap())?;
+    let mut temp_name = path.file_name().unwrap().to_owned();
+    temp_name.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let temp_path = path.with_file_name(temp_name);
+    write(&temp_path, contents)
+        .with_context(|| format!("failed to write `{}`", temp_path.display()))?;
+    rename(&temp_path, path).with_context(|| format!("failed to write `{}`", path.display()))
+}
+
+pub(crate) fn calibration_path() -> Result<PathBuf> {
+    Ok(dirs::config_dir()
+        .context("no config directory available")?
+        .join("advent-of-code-rs")
+        .join("calibration.json"))
+}
+
+/// Applies the formatter of the solution, unless an integer result should have its digits grouped.
+fn display_result(format: FormatFn, result: &PuzzleResult, group_digits: bool) -> String {
+    match result {
+        PuzzleResult::Int(result) if group_digits => result.separate_with_commas(),
+        PuzzleResult::Int64(result) if group_digits => result.separate_with_commas(),
+        PuzzleResult::UInt64(result) if group_digits => result.separate_with_commas(),
+        _ => format(result),
+    }
+}
+
+fn load_answers() -> Result<Answers> {
+    match read_to_string(ANSWERS_FILE) {
+        Ok(answers) => serde_json::from_str(&answers)
+            .with_context(|| format!("failed to parse `{ANSWERS_FILE}`")),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(Answers::new()),
+        Err(error) => Err(error).with_context(|| format!("failed to read `{ANSWERS_FILE}`")),
+    }
+}
+
+/// Lists every part of the implemented days as a checklist, grouped by year.
+pub(crate) fn print_todos() -> Result<()> {
+    let mut days = Puzzle::IMPLEMENTED_DAYS.to_vec();
+    days.sort_unstable();
+    let mut current_year = None;
+    let mut todos = 0;
+    for (year, day) in days {
+        if current_year.replace(year) != Some(year) {
+            println!("{year}");
+        }
+        for part in [PuzzlePart::Part1, PuzzlePart::Part2] {
+            let puzzle = Puzzle::new(year, day, part)?;
+            let done = !puzzle.is_todo();
+            if !done {
+                todos += 1;
+            }
+            println!(
+                "  [{}] Day {day:>2} Part {}",
+                if done { 'x' } else { ' ' },
+                part.number()
+            );
+        }
+    }
+    println!();
+    println!("{todos} part(s) left to do");
+    Ok(())
+}
+
+/// Parses an integer in the given base, accepting an optional sign and `0x`/`0o`/`0b` prefix.
+fn parse_int_in_base(value: &str, base: u32) -> Option<i128> {
+    let value = value.trim();
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let prefix = match base {
+        2 => "0b",
+        8 => "0o",
+        16 => "0x",
+        _ => "",
+    };
+    let digits = digits.strip_prefix(prefix).unwrap_or(digits);
+    let value = i128::from_str_radix(digits, base).ok()?;
+    Some(if negative { -value } else { value })
+}
+
+impl PuzzlePart {
+    pub(crate) fn number(self) -> u8 {
+        match self {
+            PuzzlePart::Part1 => 1,
+            PuzzlePart::Part2 => 2,
         }
     }
 }
@@ -418,18 +3383,255 @@ impl std::fmt::Display for PuzzleResult {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             PuzzleResult::Int(result) => write!(f, "{result}"),
+            PuzzleResult::Int64(result) => write!(f, "{result}"),
+            PuzzleResult::UInt64(result) => write!(f, "{result}"),
             PuzzleResult::Str(result) => write!(f, "{result}"),
+            PuzzleResult::Grid(lines) => write!(f, "{}", lines.join("\n")),
+        }
+    }
+}
+
+/// Sets the solution aliases from the `[aliases]` table of the config, once at startup.
+pub(crate) fn init_aliases(aliases: BTreeMap<String, String>) {
+    // Only ever called once; a second call keeps the first aliases.
+    let _ = ALIASES.set(aliases);
+}
+
+/// Looks up the solution name for an alias from the config.
+fn resolve_alias(alias: &str) -> Option<&'static str> {
+    ALIASES.get()?.get(alias).map(String::as_str)
+}
+
+/// Shortens the input to at most `max_len` bytes, or leaves it untouched if `max_len` is zero.
+fn truncate_input(input: &str, max_len: usize) -> Cow<'_, str> {
+    if max_len == 0 || input.len() <= max_len {
+        return Cow::Borrowed(input);
+    }
+    let end = (0..=max_len)
+        .rev()
+        .find(|&end| input.is_char_boundary(end))
+        .unwrap();
+    Cow::Owned(format!(
+        "{}... ({} more bytes)",
+        &input[..end],
+        input.len() - end
+    ))
+}
+
+/// Names the fastest and slowest of the results sorted by average, and how many of them differ
+/// from the first solution.
+fn print_comparison_summary(
+    benchmark_results: &[(&str, PuzzleResult, BenchmarkResult)],
+    mismatches: usize,
+) {
+    let (Some((fastest_name, _, fastest)), Some((slowest_name, _, slowest))) =
+        (benchmark_results.first(), benchmark_results.last())
+    else {
+        return;
+    };
+    if benchmark_results.len() < 2 {
+        return;
+    }
+    let speedup = slowest.average.as_secs_f64() / fastest.average.as_secs_f64();
+    ascii_print!("  {fastest_name} is fastest, {speedup:.2}× faster than {slowest_name};");
+    let matching = benchmark_results.len() - mismatches;
+    if mismatches == 0 {
+        ascii_println!(" all {matching} results match");
+    } else {
+        ascii_println!(
+            " {matching} of {} results match, {}",
+            benchmark_results.len(),
+            Color::Yellow.paint(format!("{mismatches} differ"))
+        );
+    }
+}
+
+fn warn_small_input(input: &str, settings: &BenchmarkSettings) {
+    if !settings.allow_small_input && input.len() < SMALL_INPUT_THRESHOLD {
+        ascii_println!(
+            "{}",
+            Color::Yellow.paint(format!(
+                "WARNING: Input is only {} bytes; benchmarking an example gives misleading results",
+                input.len()
+            ))
+        );
+        ascii_println!(
+            "{}",
+            Color::Yellow.paint("         Pass --allow-small-input to silence this warning")
+        );
+        println!();
+    }
+}
+
+/// How often a request is attempted before giving up on transient failures.
+const MAX_REQUEST_ATTEMPTS: u32 = 3;
+/// Wait before the first retry, doubled for every further one.
+const INITIAL_RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Curl exit codes for failures that might not happen on the next attempt: failed to connect,
+/// timed out, empty reply, send and receive errors.
+const TRANSIENT_CURL_EXIT_CODES: [i32; 5] = [7, 28, 52, 55, 56];
+
+/// A request failure that might not happen again, e.g. a server error, so it is worth retrying.
+#[derive(Debug)]
+struct TransientError(String);
+
+impl std::fmt::Display for TransientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for TransientError {}
+
+/// Whether retrying the request might succeed; client errors like 4xx responses are not retried.
+fn is_transient(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause.is::<TransientError>()
+            || cause
+                .downcast_ref::<reqwest::Error>()
+                .is_some_and(|error| error.is_timeout() || error.is_connect() || error.is_body())
+    })
+}
+
+fn get_with_curl(cookie_header: &str, url: &str, timeout: Duration) -> Result<String> {
+    run_curl(cookie_header, url, timeout, [])
+}
+
+fn run_curl(
+    cookie_header: &str,
+    url: &str,
+    timeout: Duration,
+    extra_args: impl IntoIterator<Item = String>,
+) -> Result<String> {
+    // The cookie is passed as a config on stdin to keep it out of the process list.
+    let mut curl = Command::new("curl")
+        .args(["--silent", "--show-error", "--config", "-", url])
+        .args(["--max-time".to_string(), timeout.as_secs_f64().to_string()])
+        .args(extra_args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to run curl")?;
+    writeln!(
+        curl.stdin.take().unwrap(),
+        "header = \"cookie: {cookie_header}\""
+    )?;
+    let output = curl.wait_with_output()?;
+    if !output.status.success() {
+        let message = format!(
+            "curl failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        if output
+            .status
+            .code()
+            .is_some_and(|code| TRANSIENT_CURL_EXIT_CODES.contains(&code))
+        {
+            bail!(TransientError(message));
         }
+        bail!(message);
+    }
+    String::from_utf8(output.stdout).context("response is not valid UTF-8")
+}
+
+fn post_with_curl(
+    cookie_header: &str,
+    url: &str,
+    form: &[(&str, &str)],
+    timeout: Duration,
+) -> Result<String> {
+    let form = form
+        .iter()
+        .flat_map(|(key, value)| ["--data-urlencode".to_string(), format!("{key}={value}")]);
+    run_curl(cookie_header, url, timeout, form)
+}
+
+/// Formats the time in the unit, without the unit unless it is picked automatically.
+fn format_time(time: Duration, unit: TimeUnit) -> String {
+    match unit {
+        TimeUnit::Auto => format!("{time:.2?}"),
+        TimeUnit::Ns => time.as_nanos().to_string(),
+        TimeUnit::Us => format!("{:.2}", time.as_secs_f64() * 1e6),
+        TimeUnit::Ms => format!("{:.3}", time.as_secs_f64() * 1e3),
+    }
+}
+
+fn unit_suffix(unit: TimeUnit) -> &'static str {
+    match unit {
+        TimeUnit::Auto => "",
+        TimeUnit::Ns => "ns",
+        TimeUnit::Us => "µs",
+        TimeUnit::Ms => "ms",
+    }
+}
+
+/// The header of a table column of times, mentioning the unit if it is fixed, since the times
+/// themselves don't.
+fn time_header(auto_header: &str, label: &str, width: usize, unit: TimeUnit) -> String {
+    match unit {
+        TimeUnit::Auto => auto_header.to_string(),
+        unit => format!("{:━>width$}", format!(" {label} [{}] ", unit_suffix(unit))),
+    }
+}
+
+/// Formats the number of bytes with a binary unit, e.g. `1.5 KiB`, fitting in 10 characters.
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+fn read_cycle_counter() -> u64 {
+    #[cfg(target_arch = "x86_64")]
+    // SAFETY: The timestamp counter is available on every x86_64 CPU.
+    #[allow(unused_unsafe)]
+    unsafe {
+        std::arch::x86_64::_rdtsc()
     }
+    #[cfg(not(target_arch = "x86_64"))]
+    unreachable!("cycle counter is only available on x86_64")
+}
+
+/// Formats the time as `HH:MM:SS`, with hours going past 24 if needed.
+fn format_countdown(time: Duration) -> String {
+    let seconds = time.as_secs();
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
 }
 
-fn advent_of_code_now() -> DateTime<Tz> {
+pub(crate) fn advent_of_code_now() -> DateTime<Tz> {
     Utc::now().with_timezone(&EST)
 }
 
 macro_rules! puzzles {
-    ( $( $year:literal => [ $( $day:literal )* ] )* ) => {
+    ( $( $year:literal => [ $( $day:literal $(,)? )* ] $(,)? )* ) => {
         impl Puzzle {
+            const IMPLEMENTED_DAYS: &'static [(u32, u8)] = &[ $( $( ($year, $day), )* )* ];
+
+            fn get_source(self) -> Option<&'static str> {
+                match u32::from(self.year) {
+                    $( $year => match u8::from(self.day) {
+                        $( $day => Some(include_str!(concat!("year_", $year, "/day_", $day, ".rs"))), )*
+                        _ => None,
+                    } )*
+                    _ => None,
+                }
+            }
+
             pub(crate) fn get_solutions(self) -> &'static [Solution]{
                 match u32::from(self.year) {
                     $( $year => match u8::from(self.day) {
@@ -455,6 +3657,19 @@ macro_rules! puzzles {
                     _ => &[],
                 }
             }
+
+            pub(crate) fn get_generator(self) -> Option<GeneratorFn> {
+                match u32::from(self.year) {
+                    $( $year => match u8::from(self.day) {
+                        $( $day => match self.part {
+                            PuzzlePart::Part1 => <(AdventOfCode<$year>, Day<$day>) as Part<1>>::GENERATOR,
+                            PuzzlePart::Part2 => <(AdventOfCode<$year>, Day<$day>) as Part<2>>::GENERATOR,
+                        })*
+                        _ => None,
+                    } )*
+                    _ => None,
+                }
+            }
         }
     };
 }
@@ -462,3 +3677,110 @@ macro_rules! puzzles {
 puzzles! {
     2015 => [ 1 ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The display of 2016 day 8 after the example operations, with a highlighted pixel.
+    const MULTI_LINE_EXAMPLE: &str = "<html><body><main><article class=\"day-desc\">\
+        <p>The example ends with:</p>\
+        <pre><code>.#..#.#\n<em>#</em>.#....\n.#.....\n</code></pre>\
+        </article></main></body></html>";
+
+    #[test]
+    fn code_blocks_keep_all_lines() {
+        let blocks = scrape_code_blocks(&Html::parse_document(MULTI_LINE_EXAMPLE));
+        assert_eq!(blocks, [".#..#.#\n#.#....\n.#.....\n"]);
+    }
+
+    #[test]
+    #[should_panic = "base must be in 2..=36"]
+    fn example_base_is_checked() {
+        let _ = Example::new(0, 1).with_base(37);
+    }
+
+    #[test]
+    fn solutions_run_by_name() -> Result<()> {
+        let part1 = Puzzle::new(2015, 1, PuzzlePart::Part1)?;
+        assert_eq!(part1.run_named("count", "(()")?, PuzzleResult::Int(1));
+        assert_eq!(part1.run_named("#2", "))(")?, PuzzleResult::Int(-1));
+        let part2 = Puzzle::new(2015, 1, PuzzlePart::Part2)?;
+        assert_eq!(part2.run_named("for-loop", "()())")?, PuzzleResult::Int(5));
+
+        let error = part1.run_named("unknown", "()").unwrap_err().to_string();
+        assert!(error.contains("solution `unknown` not found"), "{error}");
+        assert!(error.contains("count"), "{error}");
+        Ok(())
+    }
+
+    #[test]
+    fn default_solution_does_not_depend_on_order() {
+        let first = Solution::new("first", |_| PuzzleResult::ok(1));
+        let flagged = Solution::new("flagged", |_| PuzzleResult::ok(2)).as_default();
+        let pick = |solutions: &[Solution]| default_solution(solutions).map(|s| s.name);
+        assert_eq!(pick(&[first, flagged]), Some("flagged"));
+        assert_eq!(pick(&[flagged, first]), Some("flagged"));
+        assert_eq!(pick(&[first]), Some("first"));
+        assert_eq!(pick(&[]), None);
+    }
+
+    #[test]
+    fn title_is_scraped_from_heading() {
+        let page = Html::parse_document(
+            "<html><body><main><article class=\"day-desc\">\
+             <h2>--- Day 1: Not Quite Lisp ---</h2><p>Santa is trying...</p>\
+             </article></main></body></html>",
+        );
+        assert_eq!(scrape_title(&page).as_deref(), Some("Not Quite Lisp"));
+        assert_eq!(
+            scrape_title(&Html::parse_document(MULTI_LINE_EXAMPLE)),
+            None
+        );
+    }
+
+    #[test]
+    fn multi_line_results_match_code_blocks() {
+        let expected = &scrape_code_blocks(&Html::parse_document(MULTI_LINE_EXAMPLE))[0];
+        let lines = [".#..#.#", "#.#....", ".#....."].map(String::from);
+        assert!(PuzzleResult::Str(lines.join("\n")).matches(expected, CompareMode::Exact));
+        assert!(PuzzleResult::Grid(lines.to_vec()).matches(expected, CompareMode::Exact));
+        assert!(PuzzleResult::Grid(lines.to_vec()).matches(expected, CompareMode::Value));
+        assert!(!PuzzleResult::Str(lines[..2].join("\n")).matches(expected, CompareMode::Exact));
+    }
+
+    #[test]
+    fn error_responses_are_not_inputs() {
+        for (response, error) in [
+            (
+                "Puzzle inputs differ by user.  Please log in to get your puzzle input.\n",
+                "session is invalid or expired",
+            ),
+            (
+                "Please don't repeatedly request this endpoint before it unlocks! The calendar \
+                 countdown is synchronized with the server time; the link will be enabled on the \
+                 calendar the instant this puzzle becomes available.\n",
+                "not unlocked yet",
+            ),
+            (
+                "<!DOCTYPE html>\n<html lang=\"en-us\">\n<head>\n<title>Advent of Code</title>",
+                "session is probably expired",
+            ),
+            (
+                "<html>\n<head><title>500 Internal Server Error</title></head>\n</html>\n",
+                "session is probably expired",
+            ),
+            ("", "empty input"),
+        ] {
+            let message = check_input_response(response).unwrap_err().to_string();
+            assert!(message.contains(error), "{message:?} for {response:?}");
+        }
+    }
+
+    #[test]
+    fn inputs_are_accepted() {
+        for input in ["(()(()(\n", "1721\n979\n366\n", "<x=-1, y=0, z=2>\n"] {
+            check_input_response(input).unwrap();
+        }
+    }
+}
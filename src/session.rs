@@ -0,0 +1,322 @@
+use std::{
+    borrow::Cow,
+    cell::{Cell, OnceCell},
+    env::VarError,
+    fs::{create_dir_all, read_to_string, write},
+    io::{stdin, stdout, Write},
+    path::{Path, PathBuf},
+    thread::sleep,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+use reqwest::blocking::Client;
+
+use crate::{
+    cmd::{Args, FetchBackend},
+    puzzle::{cache_dir, Puzzle, PuzzlePart, ADVENT_OF_CODE_URL},
+};
+
+pub(crate) const ADVENT_OF_CODE_SESSION: &str = "ADVENT_OF_CODE_SESSION";
+pub(crate) const ADVENT_OF_CODE_SESSION_FILE: &str = "ADVENT_OF_CODE_SESSION_FILE";
+pub(crate) const ADVENT_OF_CODE_COOKIE_NAME: &str = "ADVENT_OF_CODE_COOKIE_NAME";
+const ADVENT_OF_CODE_MIN_INTERVAL: &str = "ADVENT_OF_CODE_MIN_INTERVAL";
+const ADVENT_OF_CODE_TIMEOUT: &str = "ADVENT_OF_CODE_TIMEOUT";
+
+const DEFAULT_COOKIE_NAME: &str = "session";
+const DEFAULT_RATE_LIMIT: Duration = Duration::from_secs(3);
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Remembers the time of the last request across runs, so rapid iteration doesn't flood the
+/// servers either.
+const LAST_REQUEST_FILE: &str = "last_request";
+
+#[cfg(feature = "keyring")]
+const KEYRING_SERVICE: &str = "advent-of-code-rs";
+#[cfg(feature = "keyring")]
+const KEYRING_USER: &str = "session";
+
+/// Returns the session together with a description of where it was found.
+///
+/// `--session-file` takes precedence over the keyring, followed by the file named by the
+/// `ADVENT_OF_CODE_SESSION_FILE` env var and finally the `ADVENT_OF_CODE_SESSION` env var.
+pub(crate) fn get_session_with_source(args: &Args) -> Result<(String, Cow<'static, str>)> {
+    if let Some(path) = &args.session_file {
+        return Ok((read_session_file(path)?, path.display().to_string().into()));
+    }
+
+    #[cfg(feature = "keyring")]
+    if let Some(session) = get_keyring_session()? {
+        return Ok((session, "keyring".into()));
+    }
+
+    if let Some(path) = std::env::var_os(ADVENT_OF_CODE_SESSION_FILE) {
+        let path = PathBuf::from(path);
+        let source = format!("{ADVENT_OF_CODE_SESSION_FILE}: {}", path.display());
+        return Ok((read_session_file(&path)?, source.into()));
+    }
+
+    match std::env::var(ADVENT_OF_CODE_SESSION) {
+        Ok(session) => Ok((session, ADVENT_OF_CODE_SESSION.into())),
+        Err(VarError::NotPresent) => {
+            bail!("{ADVENT_OF_CODE_SESSION} or {ADVENT_OF_CODE_SESSION_FILE} env var required to get puzzle input")
+        }
+        Err(error) => Err(error)?,
+    }
+}
+
+/// Reads the session from the file, ignoring surrounding whitespace like a trailing newline.
+fn read_session_file(path: &Path) -> Result<String> {
+    let session = read_to_string(path)
+        .with_context(|| format!("failed to read session file `{}`", path.display()))?;
+    let session = session.trim();
+    if session.is_empty() {
+        bail!("session file `{}` is empty", path.display());
+    }
+    Ok(session.to_string())
+}
+
+/// The session cookie together with how requests using it are made.
+pub(crate) struct Session {
+    pub(crate) cookie: String,
+    /// Name of the cookie holding the session, which only differs for mirrors.
+    cookie_name: String,
+    pub(crate) fetch_backend: FetchBackend,
+    /// Skip all requests and pretend every response is empty.
+    pub(crate) dry_network: bool,
+    /// Read inputs from the cache instead of downloading them again.
+    pub(crate) use_cache: bool,
+    /// Ask for the input once more if it is not unlocked yet, since the countdown was waited for.
+    pub(crate) wait: bool,
+    /// Read the input from this file instead, or from stdin for `-`.
+    pub(crate) input_path: Option<PathBuf>,
+    /// Minimum time between the start of two requests, even across runs.
+    rate_limit: Duration,
+    /// How long a single request may take before it is aborted.
+    pub(crate) timeout: Duration,
+    last_request: Cell<Option<SystemTime>>,
+    /// Built on the first request and reused, so connections are kept alive across requests.
+    client: OnceCell<Client>,
+}
+
+impl Session {
+    /// Sleeps until the rate limit allows another request.
+    ///
+    /// The state file is only used on a best effort basis, since failing to read or write it
+    /// should never prevent a request.
+    pub(crate) fn wait_for_rate_limit(&self) {
+        let state_path = cache_dir().ok().map(|dir| dir.join(LAST_REQUEST_FILE));
+        let last_request = self
+            .last_request
+            .get()
+            .max(state_path.as_deref().and_then(read_last_request));
+        if let Some(last_request) = last_request {
+            let elapsed = last_request.elapsed().unwrap_or_default();
+            let wait = self.rate_limit.saturating_sub(elapsed);
+            if !wait.is_zero() {
+                eprintln!(
+                    "Waiting {:.1}s to respect the rate limit...",
+                    wait.as_secs_f64()
+                );
+                sleep(wait);
+            }
+        }
+
+        let now = SystemTime::now();
+        self.last_request.set(Some(now));
+        if let Some(state_path) = state_path {
+            let millis = now
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis();
+            let _ = create_dir_all(state_path.parent().unwrap())
+                .and_then(|()| write(state_path, millis.to_string()));
+        }
+    }
+
+    /// The HTTP client shared by all requests of this session.
+    pub(crate) fn client(&self) -> Result<&Client> {
+        if let Some(client) = self.client.get() {
+            return Ok(client);
+        }
+        let client = Client::builder().timeout(self.timeout).build()?;
+        Ok(self.client.get_or_init(|| client))
+    }
+
+    /// Value of the `cookie` header for requests.
+    pub(crate) fn cookie_header(&self) -> String {
+        format!("{}={}", self.cookie_name, self.cookie)
+    }
+
+    /// Uses another cookie while keeping the settings and rate limit of this session.
+    pub(crate) fn with_other_cookie(&self, cookie: String) -> Self {
+        Self {
+            cookie,
+            cookie_name: self.cookie_name.clone(),
+            fetch_backend: self.fetch_backend,
+            dry_network: self.dry_network,
+            use_cache: self.use_cache,
+            wait: self.wait,
+            input_path: self.input_path.clone(),
+            rate_limit: self.rate_limit,
+            timeout: self.timeout,
+            last_request: self.last_request.clone(),
+            client: self.client.clone(),
+        }
+    }
+
+    pub(crate) fn with_cookie(cookie: String, args: &Args) -> Self {
+        Self {
+            cookie,
+            cookie_name: get_cookie_name(),
+            fetch_backend: args.fetch_backend,
+            dry_network: args.dry_network,
+            use_cache: !args.no_cache,
+            wait: args.wait,
+            input_path: args.input.clone(),
+            rate_limit: resolve_rate_limit(args),
+            timeout: resolve_timeout(args),
+            last_request: Cell::new(None),
+            client: OnceCell::new(),
+        }
+    }
+}
+
+fn read_last_request(path: &Path) -> Option<SystemTime> {
+    let millis = read_to_string(path).ok()?.trim().parse().ok()?;
+    Some(UNIX_EPOCH + Duration::from_millis(millis))
+}
+
+/// Uses `--rate-limit`, falling back to the `ADVENT_OF_CODE_MIN_INTERVAL` env var in seconds.
+pub(crate) fn resolve_rate_limit(args: &Args) -> Duration {
+    if let Some(rate_limit) = args.rate_limit {
+        return Duration::from_millis(rate_limit);
+    }
+    std::env::var(ADVENT_OF_CODE_MIN_INTERVAL)
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds.trim().parse().ok()?).ok())
+        .unwrap_or(DEFAULT_RATE_LIMIT)
+}
+
+/// Uses `--timeout`, falling back to the `ADVENT_OF_CODE_TIMEOUT` env var, both in seconds.
+pub(crate) fn resolve_timeout(args: &Args) -> Duration {
+    args.timeout
+        .or_else(|| {
+            std::env::var(ADVENT_OF_CODE_TIMEOUT)
+                .ok()?
+                .trim()
+                .parse()
+                .ok()
+        })
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .unwrap_or(DEFAULT_TIMEOUT)
+}
+
+pub(crate) fn get_session(args: &Args) -> Result<Session> {
+    // Neither needs a session, since nothing is downloaded.
+    if args.dry_network || args.input.is_some() {
+        return Ok(Session::with_cookie(String::new(), args));
+    }
+    Ok(Session::with_cookie(get_session_with_source(args)?.0, args))
+}
+
+pub(crate) fn get_cookie_name() -> String {
+    std::env::var(ADVENT_OF_CODE_COOKIE_NAME).unwrap_or_else(|_| DEFAULT_COOKIE_NAME.to_string())
+}
+
+fn prompt_session() -> Result<String> {
+    print!("Session: ");
+    stdout().flush()?;
+    let mut session = String::new();
+    stdin().read_line(&mut session)?;
+    let session = session.trim();
+    if session.is_empty() {
+        bail!("no session given");
+    }
+    Ok(session.to_string())
+}
+
+/// Prompts for the session, checks that it works and stores it in the keyring or `.env` file.
+#[cfg_attr(feature = "keyring", allow(unused_variables))]
+pub(crate) fn setup(args: &Args, dotenv_path: Option<&Path>) -> Result<()> {
+    println!("Log in on {ADVENT_OF_CODE_URL} and copy the value of the `session` cookie,");
+    println!("e.g. from the storage tab of your browser's developer tools.");
+    println!();
+
+    let session = Session::with_cookie(prompt_session()?, args);
+    print!("Checking session... ");
+    stdout().flush()?;
+    let user = Puzzle::new(2015, 1, PuzzlePart::Part1)?
+        .get_user_name(&session)?
+        .context("session is invalid or expired")?;
+    println!("session valid, user: {user}");
+
+    #[cfg(feature = "keyring")]
+    {
+        keyring_entry()?
+            .set_password(&session.cookie)
+            .context("failed to store session in keyring")?;
+        println!("Session stored in keyring.");
+    }
+    #[cfg(not(feature = "keyring"))]
+    {
+        let path = dotenv_path.unwrap_or(Path::new(".env"));
+        store_dotenv_session(path, &session.cookie)?;
+        println!("Session stored in {}.", path.display());
+    }
+
+    Ok(())
+}
+
+/// Replaces the session in the `.env` file or appends it if there is none yet.
+#[cfg(not(feature = "keyring"))]
+fn store_dotenv_session(path: &Path, session: &str) -> Result<()> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(error) => {
+            return Err(error).with_context(|| format!("failed to read `{}`", path.display()))
+        }
+    };
+    let prefix = format!("{ADVENT_OF_CODE_SESSION}=");
+    let mut lines = contents
+        .lines()
+        .filter(|line| !line.starts_with(&prefix))
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>();
+    lines.push(format!("{prefix}{session}"));
+    std::fs::write(path, lines.join("\n") + "\n")
+        .with_context(|| format!("failed to write `{}`", path.display()))
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_entry() -> Result<keyring::Entry> {
+    Ok(keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)?)
+}
+
+#[cfg(feature = "keyring")]
+fn get_keyring_session() -> Result<Option<String>> {
+    match keyring_entry()?.get_password() {
+        Ok(session) => Ok(Some(session)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(error) => Err(error).context("failed to read session from keyring"),
+    }
+}
+
+#[cfg(feature = "keyring")]
+pub(crate) fn store_session() -> Result<()> {
+    let session = prompt_session()?;
+    keyring_entry()?
+        .set_password(&session)
+        .context("failed to store session in keyring")?;
+    println!("Session stored in keyring.");
+
+    Ok(())
+}
+
+pub(crate) fn mask_secret(secret: &str) -> String {
+    let suffix_start = secret.len().saturating_sub(4);
+    match secret.get(suffix_start..) {
+        Some(suffix) if suffix_start >= 8 => format!("********{suffix}"),
+        _ => "********".to_string(),
+    }
+}
@@ -0,0 +1,186 @@
+use std::f64::consts::PI;
+
+/// Performs Welch's t-test and returns the two-sided p-value for the means of both samples being
+/// equal, or [`None`] if either sample is too small or has no variance.
+pub(crate) fn welch_t_test(a: &[f64], b: &[f64]) -> Option<f64> {
+    let (mean_a, var_a) = mean_and_variance(a)?;
+    let (mean_b, var_b) = mean_and_variance(b)?;
+    let se_a = var_a / a.len() as f64;
+    let se_b = var_b / b.len() as f64;
+    let se = se_a + se_b;
+    if se == 0.0 {
+        return None;
+    }
+
+    let t = (mean_a - mean_b) / se.sqrt();
+    let df =
+        se.powi(2) / (se_a.powi(2) / (a.len() - 1) as f64 + se_b.powi(2) / (b.len() - 1) as f64);
+    // Two-sided p-value using the CDF of Student's t-distribution.
+    Some(regularized_incomplete_beta(
+        df / 2.0,
+        0.5,
+        df / (df + t * t),
+    ))
+}
+
+fn mean_and_variance(samples: &[f64]) -> Option<(f64, f64)> {
+    if samples.len() < 2 {
+        return None;
+    }
+    let n = samples.len() as f64;
+    let mean = samples.iter().sum::<f64>() / n;
+    let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    Some((mean, variance))
+}
+
+/// The regularized incomplete beta function `I_x(a, b)`, evaluated with a continued fraction.
+fn regularized_incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    // The continued fraction converges quickly only below this point; use the symmetry otherwise.
+    if x > (a + 1.0) / (a + b + 2.0) {
+        return 1.0 - regularized_incomplete_beta(b, a, 1.0 - x);
+    }
+
+    let ln_front = ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln();
+    ln_front.exp() * beta_continued_fraction(a, b, x) / a
+}
+
+/// Evaluates the continued fraction of the incomplete beta function using Lentz's method.
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const MAX_ITERATIONS: u32 = 300;
+    const EPSILON: f64 = 1e-14;
+    const TINY: f64 = 1e-300;
+
+    let clamp = |value: f64| if value.abs() < TINY { TINY } else { value };
+
+    let mut c = 1.0;
+    let mut d = 1.0 / clamp(1.0 - (a + b) * x / (a + 1.0));
+    let mut result = d;
+    for m in 1..=MAX_ITERATIONS {
+        let m = f64::from(m);
+        let even = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 / clamp(1.0 + even * d);
+        c = clamp(1.0 + even / c);
+        result *= d * c;
+
+        let odd = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 / clamp(1.0 + odd * d);
+        c = clamp(1.0 + odd / c);
+        let delta = d * c;
+        result *= delta;
+
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    result
+}
+
+/// Natural logarithm of the gamma function using the Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        // Reflection formula for the left half-plane.
+        return (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let sum = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, coefficient)| {
+            sum + coefficient / (x + i as f64 + 1.0)
+        });
+    0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9 * expected.abs().max(1.0),
+            "{actual} != {expected}"
+        );
+    }
+
+    #[test]
+    fn welch_t_test_matches_reference_p_values() {
+        // R's `t.test(extra ~ group, data = sleep)`: t = -1.8608, df = 17.776, p-value = 0.07939
+        let group_1 = [0.7, -1.6, -0.2, -1.2, -0.1, 3.4, 3.7, 0.8, 0.0, 2.0];
+        let group_2 = [1.9, 0.8, 1.1, 0.1, -0.1, 4.4, 5.5, 1.6, 4.6, 3.4];
+        assert_close(
+            welch_t_test(&group_1, &group_2).unwrap(),
+            0.079_394_140_187_358_14,
+        );
+        assert_close(
+            welch_t_test(&group_2, &group_1).unwrap(),
+            0.079_394_140_187_358_14,
+        );
+
+        // t = -3/√2 with df = 2, where the two-sided p-value is exactly 1 - 3/√13.
+        assert_close(
+            welch_t_test(&[1.0, 3.0], &[4.0, 6.0]).unwrap(),
+            1.0 - 3.0 / 13f64.sqrt(),
+        );
+
+        // Unequal sizes and variances: t = -3.1436, df = 11.845
+        let a = [10.1, 9.8, 10.3, 10.0, 9.9, 10.2];
+        let b = [10.0, 10.4, 10.6, 10.5, 10.3, 10.9, 10.2, 10.7];
+        assert_close(welch_t_test(&a, &b).unwrap(), 0.008_597_925_295_966_574);
+    }
+
+    #[test]
+    fn welch_t_test_of_equal_means_is_not_significant() {
+        assert_close(
+            welch_t_test(&[1.0, 2.0, 3.0], &[0.0, 2.0, 4.0]).unwrap(),
+            1.0,
+        );
+    }
+
+    #[test]
+    fn welch_t_test_rejects_degenerate_samples() {
+        assert_eq!(welch_t_test(&[], &[1.0, 2.0]), None);
+        assert_eq!(welch_t_test(&[1.0], &[1.0, 2.0]), None);
+        assert_eq!(welch_t_test(&[1.0, 2.0], &[3.0]), None);
+        assert_eq!(welch_t_test(&[1.0, 1.0], &[2.0, 2.0, 2.0]), None);
+    }
+
+    #[test]
+    fn ln_gamma_matches_factorials() {
+        assert_close(ln_gamma(1.0), 0.0);
+        assert_close(ln_gamma(2.0), 0.0);
+        assert_close(ln_gamma(10.0), 362_880f64.ln());
+        assert_close(ln_gamma(0.5), PI.sqrt().ln());
+        assert_close(ln_gamma(0.25), 3.625_609_908_221_908f64.ln());
+    }
+
+    #[test]
+    fn regularized_incomplete_beta_matches_closed_forms() {
+        for x in [0.0, 0.1, 0.5, 0.9, 1.0] {
+            // I_x(1, 1) = x and I_x(a, 1) = x^a
+            assert_close(regularized_incomplete_beta(1.0, 1.0, x), x);
+            assert_close(regularized_incomplete_beta(3.0, 1.0, x), x.powi(3));
+        }
+        // Symmetric around 1/2 for a = b.
+        assert_close(regularized_incomplete_beta(7.5, 7.5, 0.5), 0.5);
+    }
+}
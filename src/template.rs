@@ -1,15 +1,17 @@
 use std::{
     collections::{BTreeMap, BTreeSet},
     fs::{create_dir_all, read_to_string, File, OpenOptions},
-    io::{stdout, Write},
+    io::{stdout, ErrorKind, Write},
 };
 
 use anyhow::{bail, Context, Result};
 
 use crate::puzzle::{PuzzleDay, PuzzleYear};
 
-pub(crate) fn generate_template(year: PuzzleYear, day: PuzzleDay) -> Result<()> {
-    create_template_file(year, day)?;
+/// Creates the solution file unless it exists or `force` is set, and makes sure it is wired up,
+/// so this can also repair a partially generated day.
+pub(crate) fn generate_template(year: PuzzleYear, day: PuzzleDay, force: bool) -> Result<()> {
+    create_template_file(year, day, force)?;
     add_day_to_year_mod(year, day)?;
     add_year_to_main(year)?;
     add_puzzle_to_macro(year, day)?;
@@ -17,32 +19,55 @@ pub(crate) fn generate_template(year: PuzzleYear, day: PuzzleDay) -> Result<()>
     Ok(())
 }
 
-fn create_template_file(year: PuzzleYear, day: PuzzleDay) -> Result<()> {
+fn create_template_file(year: PuzzleYear, day: PuzzleDay, force: bool) -> Result<()> {
     print!("Creating template for year {year} day {day}... ");
     stdout().flush()?;
 
     let year_dir = format!("src/year_{year}");
     create_dir_all(&year_dir)?;
 
-    let mut file = OpenOptions::new()
+    let path = format!("{year_dir}/day_{day}.rs");
+    let mut file = match OpenOptions::new()
         .write(true)
-        .create_new(true)
-        .open(format!("{year_dir}/day_{day}.rs"))?;
+        .create_new(!force)
+        .create(force)
+        .truncate(force)
+        .open(&path)
+    {
+        Ok(file) => file,
+        Err(error) if error.kind() == ErrorKind::AlreadyExists => {
+            println!("Day already exists, skipping file creation (use --force to overwrite)");
+            return Ok(());
+        }
+        Err(error) => return Err(error).with_context(|| format!("failed to create `{path}`")),
+    };
 
     write!(
         file,
-        r#"use crate::puzzle::{{AdventOfCode, Day, Example, Part, Solution}};
+        r#"use anyhow::Result;
 
-impl Part<1> for (AdventOfCode<{year}>, Day<{day}>) {{
-    const SOLUTIONS: &'static [Solution] = &[Solution("solution", |_input| todo!())];
+use crate::puzzle::{{AdventOfCode, Day, Example, Part, PuzzleResult, Solution}};
 
-    const EXAMPLES: &'static [Example] = &[];
+impl Part<1> for (AdventOfCode<{year}>, Day<{day}>) {{
+    const SOLUTIONS: &'static [Solution] = &[Solution::new(
+        "solution",
+        |_input| -> Result<PuzzleResult> {{ todo!() }},
+    )];
+
+    const EXAMPLES: &'static [Example] = &[
+        // Example::new(input_offset, expected_result_offset),
+    ];
 }}
 
 impl Part<2> for (AdventOfCode<{year}>, Day<{day}>) {{
-    const SOLUTIONS: &'static [Solution] = &[Solution("solution", |_input| todo!())];
-
-    const EXAMPLES: &'static [Example] = &[];
+    const SOLUTIONS: &'static [Solution] = &[Solution::new(
+        "solution",
+        |_input| -> Result<PuzzleResult> {{ todo!() }},
+    )];
+
+    const EXAMPLES: &'static [Example] = &[
+        // Example::new(input_offset, expected_result_offset),
+    ];
 }}
 "#
     )?;
@@ -61,7 +86,7 @@ fn add_day_to_year_mod(year: PuzzleYear, day: PuzzleDay) -> Result<()> {
 
     let contents = match read_to_string(&mod_path) {
         Ok(content) => content,
-        Err(error) if error.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(error) if error.kind() == ErrorKind::NotFound => String::new(),
         Err(error) => Err(error)?,
     };
 
@@ -84,28 +109,59 @@ fn add_year_to_main(year: PuzzleYear) -> Result<()> {
     stdout().flush()?;
 
     let contents = read_to_string("src/main.rs")?;
+    let contents = insert_year_mod(&contents, year);
+    File::create("src/main.rs")?.write_all(contents.as_bytes())?;
 
-    let is_mod_line = |line: &&str| line.starts_with("mod");
+    println!("Done!");
 
-    let mut mod_lines = contents
-        .lines()
-        .take_while(is_mod_line)
-        .collect::<BTreeSet<_>>();
+    Ok(())
+}
+
+/// Inserts `mod year_{year};` sorted among the other year modules, leaving all other lines as
+/// they are; without any year modules yet, it goes at the very top.
+fn insert_year_mod(contents: &str, year: PuzzleYear) -> String {
     let new_year_line = format!("mod year_{year};");
-    mod_lines.insert(&new_year_line);
+    let is_year_mod_line = |line: &str| {
+        line.strip_prefix("mod year_")
+            .and_then(|rest| rest.strip_suffix(';'))
+            .is_some_and(|year| !year.is_empty() && year.bytes().all(|byte| byte.is_ascii_digit()))
+    };
 
-    let mut file = File::create("src/main.rs")?;
-    for line in mod_lines {
-        writeln!(file, "{line}")?;
+    let lines = contents.lines().collect::<Vec<_>>();
+    if lines.contains(&new_year_line.as_str()) {
+        return contents.to_string();
     }
+    let year_lines = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| is_year_mod_line(line))
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+
+    let insert_at = match year_lines.last() {
+        Some(&last) => year_lines
+            .iter()
+            .copied()
+            .find(|&index| lines[index] > new_year_line.as_str())
+            .unwrap_or(last + 1),
+        None => 0,
+    };
 
-    for line in contents.lines().skip_while(is_mod_line) {
-        writeln!(file, "{line}")?;
+    let mut result = String::new();
+    for line in &lines[..insert_at] {
+        result.push_str(line);
+        result.push('\n');
     }
-
-    println!("Done!");
-
-    Ok(())
+    result.push_str(&new_year_line);
+    result.push('\n');
+    if year_lines.is_empty() {
+        result.push('\n');
+    }
+    for line in &lines[insert_at..] {
+        result.push_str(line);
+        result.push('\n');
+    }
+    result
 }
 
 fn add_puzzle_to_macro(year: PuzzleYear, day: PuzzleDay) -> Result<()> {
@@ -113,55 +169,179 @@ fn add_puzzle_to_macro(year: PuzzleYear, day: PuzzleDay) -> Result<()> {
     stdout().flush()?;
 
     let contents = read_to_string("src/puzzle.rs")?;
+    let contents = insert_puzzle_into_macro(&contents, year, day)?;
+    File::create("src/puzzle.rs")?.write_all(contents.as_bytes())?;
 
-    let is_puzzle_macro_start = |line: &&str| line.starts_with("puzzles! {");
-
-    let puzzle_lines = contents
-        .lines()
-        .skip_while(|line| !is_puzzle_macro_start(line))
-        .skip(1)
-        .take_while(|line| !line.starts_with('}'))
-        .collect::<BTreeSet<_>>();
-
-    let mut puzzles = puzzle_lines
-        .into_iter()
-        .map(|line| {
-            let mut iter = line.split_ascii_whitespace();
-            let year = iter.next().context("year not found")?.parse()?;
-            if iter.next() != Some("=>") {
-                bail!("`=>` expected");
-            }
-            if iter.next() != Some("[") {
-                bail!("`[` expected");
-            }
-            if iter.next_back() != Some("]") {
-                bail!("`]` expected");
-            }
-            Ok((year, iter.map(|day| day.parse()).collect::<Result<_, _>>()?))
-        })
-        .collect::<Result<BTreeMap<PuzzleYear, BTreeSet<PuzzleDay>>, _>>()?;
+    println!("Done!");
 
-    puzzles.entry(year).or_default().insert(day);
+    Ok(())
+}
 
-    let mut file = File::create("src/puzzle.rs")?;
-    let content = contents
-        .lines()
-        .take_while(|line| !is_puzzle_macro_start(line));
-    for line in content {
-        writeln!(file, "{line}")?;
-    }
+/// Years and their days as listed in the `puzzles!` macro.
+type Puzzles = BTreeMap<PuzzleYear, BTreeSet<PuzzleDay>>;
+
+/// Adds the day to the `puzzles!` macro invocation in the source of `puzzle.rs` and writes it back
+/// in its canonical form, leaving everything around the invocation untouched.
+fn insert_puzzle_into_macro(contents: &str, year: PuzzleYear, day: PuzzleDay) -> Result<String> {
+    let start = contents
+        .match_indices("puzzles! {")
+        .map(|(index, _)| index)
+        .find(|&index| index == 0 || contents[..index].ends_with('\n'))
+        .context("`puzzles!` macro not found")?;
+    let body_start = start + "puzzles! {".len();
+    let body_end = body_start
+        + contents[body_start..]
+            .find('}')
+            .context("`}` of `puzzles!` macro not found")?;
+
+    let mut puzzles = parse_puzzles(&contents[body_start..body_end])?;
+    puzzles.entry(year).or_default().insert(day);
 
-    writeln!(file, "puzzles! {{")?;
+    let mut result = contents[..start].to_string();
+    result.push_str("puzzles! {\n");
     for (year, days) in puzzles {
-        write!(file, "    {year} => [ ")?;
+        result.push_str(&format!("    {year} => [ "));
         for day in days {
-            write!(file, "{day} ")?;
+            result.push_str(&format!("{day} "));
         }
-        writeln!(file, "]")?;
+        result.push_str("]\n");
     }
-    writeln!(file, "}}")?;
+    result.push('}');
+    result.push_str(&contents[body_end + 1..]);
+    Ok(result)
+}
 
-    println!("Done!");
+/// Parses `year => [ day day ... ]` entries regardless of how they are spread across lines and
+/// whether they are separated by commas.
+fn parse_puzzles(body: &str) -> Result<Puzzles> {
+    let body = body
+        .replace("=>", " => ")
+        .replace('[', " [ ")
+        .replace(']', " ] ")
+        .replace(',', " ");
+    let mut tokens = body.split_ascii_whitespace();
+
+    let mut puzzles = Puzzles::new();
+    while let Some(year) = tokens.next() {
+        let year = year
+            .parse()
+            .with_context(|| format!("invalid year `{year}`"))?;
+        if tokens.next() != Some("=>") {
+            bail!("`=>` expected after year {year}");
+        }
+        if tokens.next() != Some("[") {
+            bail!("`[` expected after `{year} =>`");
+        }
+        let days = puzzles.entry(year).or_default();
+        loop {
+            match tokens.next() {
+                Some("]") => break,
+                Some(day) => {
+                    days.insert(
+                        day.parse()
+                            .with_context(|| format!("invalid day `{day}` in year {year}"))?,
+                    );
+                }
+                None => bail!("`]` expected to close the days of year {year}"),
+            }
+        }
+    }
+    Ok(puzzles)
+}
 
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CANONICAL: &str =
+        "use crate::puzzle::Puzzle;\n\npuzzles! {\n    2015 => [ 1 2 ]\n    2016 => [ 3 ]\n}\n";
+
+    fn insert(contents: &str, year: u32, day: u8) -> Result<String> {
+        insert_puzzle_into_macro(
+            contents,
+            PuzzleYear::new(year).unwrap(),
+            PuzzleDay::new(day).unwrap(),
+        )
+    }
+
+    const MAIN: &str = "mod year_2015;\nmod year_2017;\n\nmod cmd;\nmod puzzle;\nmod template;\n\nuse std::path::PathBuf;\n";
+
+    #[test]
+    fn year_mods_are_inserted_sorted() {
+        let year = |year| PuzzleYear::new(year).unwrap();
+        assert_eq!(
+            insert_year_mod(MAIN, year(2016)),
+            MAIN.replace("2015;\n", "2015;\nmod year_2016;\n")
+        );
+        assert_eq!(
+            insert_year_mod(MAIN, year(2018)),
+            MAIN.replace("2017;\n", "2017;\nmod year_2018;\n")
+        );
+        assert_eq!(
+            insert_year_mod(MAIN, year(2015)),
+            MAIN,
+            "existing years are left alone"
+        );
+    }
+
+    #[test]
+    fn other_mods_are_left_alone() {
+        let main = "mod cmd;\nmod puzzle;\nmod year_2015;\nmod template;\n";
+        assert_eq!(
+            insert_year_mod(main, PuzzleYear::new(2016).unwrap()),
+            "mod cmd;\nmod puzzle;\nmod year_2015;\nmod year_2016;\nmod template;\n"
+        );
+        assert_eq!(
+            insert_year_mod("mod cmd;\nmod puzzle;\n", PuzzleYear::new(2016).unwrap()),
+            "mod year_2016;\n\nmod cmd;\nmod puzzle;\n"
+        );
+    }
+
+    #[test]
+    fn canonical_macro_round_trips() {
+        assert_eq!(insert(CANONICAL, 2015, 2).unwrap(), CANONICAL);
+    }
+
+    #[test]
+    fn days_are_inserted_in_order() {
+        assert_eq!(
+            insert(CANONICAL, 2015, 25).unwrap(),
+            CANONICAL.replace("[ 1 2 ]", "[ 1 2 25 ]")
+        );
+        assert_eq!(
+            insert(CANONICAL, 2017, 1).unwrap(),
+            CANONICAL.replace("[ 3 ]\n", "[ 3 ]\n    2017 => [ 1 ]\n")
+        );
+    }
+
+    #[test]
+    fn reformatted_macros_are_canonicalized() {
+        for variant in [
+            "use crate::puzzle::Puzzle;\n\npuzzles! {\n    2015 => [\n        1\n        2\n    ]\n    2016 => [\n        3\n    ]\n}\n",
+            "use crate::puzzle::Puzzle;\n\npuzzles! { 2015 => [ 1 2 ] 2016 => [ 3 ] }\n",
+            "use crate::puzzle::Puzzle;\n\npuzzles! {\n    2015 => [1, 2,],\n    2016 => [3],\n}\n",
+            "use crate::puzzle::Puzzle;\n\npuzzles! {\n\t2015=>[1]\n    2016   =>   [   3   ]\n  2015 => [ 2 ]\n}\n",
+        ] {
+            assert_eq!(insert(variant, 2015, 1).unwrap(), CANONICAL, "{variant}");
+        }
+    }
+
+    #[test]
+    fn code_after_the_macro_is_kept() {
+        let contents = format!("{CANONICAL}\n#[cfg(test)]\nmod tests {{}}\n");
+        assert_eq!(insert(&contents, 2015, 1).unwrap(), contents);
+    }
+
+    #[test]
+    fn malformed_macros_are_rejected() {
+        for variant in [
+            "puzzles! {\n    2015 => [ 1 2\n}\n",
+            "puzzles! {\n    2015 [ 1 ]\n}\n",
+            "puzzles! {\n    2015 => [ 26 ]\n}\n",
+            "puzzles! {\n    twenty => [ 1 ]\n}\n",
+            "fn main() {}\n",
+        ] {
+            assert!(insert(variant, 2015, 1).is_err(), "{variant}");
+        }
+    }
 }
@@ -1,21 +1,27 @@
 use std::hint::unreachable_unchecked;
 
-use crate::puzzle::{AdventOfCode, Day, Example, Part, PuzzleResult, Solution};
+use anyhow::{bail, Result};
+
+use crate::puzzle::{
+    parsed_solution, AdventOfCode, Day, Example, GeneratorFn, Part, PuzzleResult, Solution,
+};
 
 impl Part<1> for (AdventOfCode<2015>, Day<1>) {
     const SOLUTIONS: &'static [Solution] = &[
-        Solution("count", |input| {
+        Solution::new("count", |input| {
             let mut floor = 0;
             for char in input.bytes() {
                 floor += match char {
                     b'(' => 1,
                     b')' => -1,
-                    _ => panic!("invalid character"),
+                    _ => bail!("invalid character"),
                 }
             }
-            PuzzleResult::Int(floor)
-        }),
-        Solution("count-unsafe", |input| {
+            PuzzleResult::ok(floor)
+        })
+        .with_description("O(n), single pass")
+        .as_default(),
+        Solution::new("count-unsafe", |input| {
             let mut floor = 0;
             for char in input.bytes() {
                 floor += match char {
@@ -24,26 +30,30 @@ impl Part<1> for (AdventOfCode<2015>, Day<1>) {
                     _ => unsafe { unreachable_unchecked() },
                 }
             }
-            PuzzleResult::Int(floor)
-        }),
-        Solution("count-twice", |input| {
+            PuzzleResult::ok(floor)
+        })
+        .with_description("O(n), single pass assuming valid input"),
+        Solution::new("count-twice", |input| {
             let count = |paren| input.bytes().filter(|&char| char == paren).count() as i32;
-            PuzzleResult::Int(count(b'(') - count(b')'))
-        }),
-        Solution("len-minus", |input| {
+            PuzzleResult::ok(count(b'(') - count(b')'))
+        })
+        .with_description("O(n), two passes counting each paren"),
+        Solution::new("len-minus", |input| {
             let closing = input.bytes().filter(|&char| matches!(char, b')')).count();
-            PuzzleResult::Int(input.len() as i32 - closing as i32 * 2)
-        }),
-        Solution("len-dec2", |input| {
+            PuzzleResult::ok(input.len() as i32 - closing as i32 * 2)
+        })
+        .with_description("O(n), counts closing parens only"),
+        Solution::new("len-dec2", |input| {
             let mut count = input.len() as i32;
             for char in input.bytes() {
                 if char == b')' {
                     count -= 2;
                 }
             }
-            PuzzleResult::Int(count)
-        }),
-        Solution("len-dec2-unsafe", |input| {
+            PuzzleResult::ok(count)
+        })
+        .with_description("O(n), single pass starting from the length"),
+        Solution::new("len-dec2-unsafe", |input| {
             let mut count = input.len() as i32;
             for char in input.bytes() {
                 if char == b')' {
@@ -52,22 +62,24 @@ impl Part<1> for (AdventOfCode<2015>, Day<1>) {
                     unsafe { unreachable_unchecked() }
                 }
             }
-            PuzzleResult::Int(count)
-        }),
-        Solution("map-sum", |input| {
-            PuzzleResult::Int(
+            PuzzleResult::ok(count)
+        })
+        .with_description("O(n), single pass starting from the length assuming valid input"),
+        Solution::new("map-sum", |input| {
+            PuzzleResult::ok(
                 input
                     .bytes()
                     .map(|char| match char {
-                        b'(' => 1,
-                        b')' => -1,
-                        _ => panic!("invalid character"),
+                        b'(' => Ok(1),
+                        b')' => Ok(-1),
+                        _ => bail!("invalid character"),
                     })
-                    .sum(),
+                    .sum::<Result<i32>>()?,
             )
-        }),
-        Solution("map-sum-unsafe", |input| {
-            PuzzleResult::Int(
+        })
+        .with_description("O(n), iterator map and sum"),
+        Solution::new("map-sum-unsafe", |input| {
+            PuzzleResult::ok(
                 input
                     .bytes()
                     .map(|char| match char {
@@ -75,41 +87,44 @@ impl Part<1> for (AdventOfCode<2015>, Day<1>) {
                         b')' => -1,
                         _ => unsafe { unreachable_unchecked() },
                     })
-                    .sum(),
+                    .sum::<i32>(),
             )
-        }),
+        })
+        .with_description("O(n), iterator map and sum assuming valid input"),
     ];
 
     const EXAMPLES: &'static [Example] = &[
-        Example(3, 5),
-        Example(4, 5),
-        Example(6, 8),
-        Example(7, 8),
-        Example(9, 10),
-        Example(11, 13),
-        Example(12, 13),
-        Example(14, 16),
-        Example(15, 16),
+        Example::new(3, 5),
+        Example::new(4, 5),
+        Example::new(6, 8),
+        Example::new(7, 8),
+        Example::new(9, 10),
+        Example::new(11, 13),
+        Example::new(12, 13),
+        Example::new(14, 16),
+        Example::new(15, 16),
     ];
+
+    const GENERATOR: Option<GeneratorFn> = Some(generate_input);
 }
 
 impl Part<2> for (AdventOfCode<2015>, Day<1>) {
     const SOLUTIONS: &'static [Solution] = &[
-        Solution("for-loop", |input| {
+        Solution::new("for-loop", |input| {
             let mut floor = 0;
             for (position, char) in input.bytes().enumerate() {
                 match char {
                     b'(' => floor += 1,
                     b')' => floor -= 1,
-                    _ => panic!("invalid character"),
+                    _ => bail!("invalid character"),
                 }
                 if floor == -1 {
-                    return PuzzleResult::Int(position as i32 + 1);
+                    return PuzzleResult::ok(position as i32 + 1);
                 }
             }
-            panic!("never entered basement");
+            bail!("never entered basement");
         }),
-        Solution("for-loop-unsafe", |input| {
+        Solution::new("for-loop-unsafe", |input| {
             let mut floor = 0;
             for (position, char) in input.bytes().enumerate() {
                 match char {
@@ -118,13 +133,151 @@ impl Part<2> for (AdventOfCode<2015>, Day<1>) {
                     _ => unsafe { unreachable_unchecked() },
                 }
                 if floor == -1 {
-                    return PuzzleResult::Int(position as i32 + 1);
+                    return PuzzleResult::ok(position as i32 + 1);
                 }
             }
 
             unsafe { unreachable_unchecked() }
         }),
+        parsed_solution!("parse-steps", parse_steps, first_basement_position)
+            .with_description("O(n), parses into steps before finding the basement"),
     ];
 
-    const EXAMPLES: &'static [Example] = &[Example(21, 22), Example(23, 24)];
+    const EXAMPLES: &'static [Example] = &[Example::new(21, 22), Example::new(23, 24)];
+
+    const GENERATOR: Option<GeneratorFn> = Some(generate_input);
+}
+
+/// Turns the parentheses into steps of `1` and `-1`.
+fn parse_steps(input: &str) -> Result<Vec<i8>> {
+    input
+        .bytes()
+        .map(|char| match char {
+            b'(' => Ok(1),
+            b')' => Ok(-1),
+            _ => bail!("invalid character"),
+        })
+        .collect()
+}
+
+fn first_basement_position(steps: &[i8]) -> Result<PuzzleResult> {
+    let mut floor = 0;
+    for (position, step) in steps.iter().enumerate() {
+        floor += i32::from(*step);
+        if floor == -1 {
+            return PuzzleResult::ok(position as i32 + 1);
+        }
+    }
+    bail!("never entered basement");
+}
+
+/// Pseudo-random parentheses staying above ground for the first half, followed by closing ones to
+/// always reach the basement, even for a size of 0.
+fn generate_input(size: usize) -> String {
+    let size = size.max(1);
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut floor = 0;
+    (0..size)
+        .map(|index| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            if index < size.saturating_sub(1) / 2 && (floor == 0 || state & 1 == 0) {
+                floor += 1;
+                '('
+            } else {
+                floor -= 1;
+                ')'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PART_1: &[Solution] = <(AdventOfCode<2015>, Day<1>) as Part<1>>::SOLUTIONS;
+    const PART_2: &[Solution] = <(AdventOfCode<2015>, Day<1>) as Part<2>>::SOLUTIONS;
+
+    /// Random parentheses of every length up to `max_len`, using a fixed seed.
+    fn random_inputs(max_len: usize) -> impl Iterator<Item = String> {
+        let mut state = 0x9e37_79b9_7f4a_7c15_u64;
+        (0..=max_len).map(move |len| {
+            (0..len)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    if state & 1 == 0 {
+                        '('
+                    } else {
+                        ')'
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn assert_all_agree(solutions: &[Solution], input: &str) {
+        let expected = (solutions[0].solve)(input).unwrap();
+        for solution in &solutions[1..] {
+            assert_eq!(
+                (solution.solve)(input).unwrap(),
+                expected,
+                "{} disagrees with {} on {input:?}",
+                solution.name,
+                solutions[0].name
+            );
+        }
+    }
+
+    #[test]
+    fn part_1_solutions_agree() {
+        for input in ["", "(", ")", "((((", "))))", "()()", "())("]
+            .into_iter()
+            .map(String::from)
+            .chain(random_inputs(256))
+        {
+            assert_all_agree(PART_1, &input);
+        }
+    }
+
+    #[test]
+    fn part_2_solutions_agree() {
+        // Closing parentheses are appended to guarantee the basement is reached, since the unsafe
+        // solution assumes it is.
+        for input in random_inputs(256) {
+            let input = input.clone() + &")".repeat(input.len() + 1);
+            assert_all_agree(PART_2, &input);
+        }
+    }
+
+    #[test]
+    fn generated_inputs_reach_the_basement() {
+        for size in 0..64 {
+            let input = generate_input(size);
+            for solution in PART_2 {
+                assert!(
+                    (solution.solve)(&input).is_ok(),
+                    "{} failed on generated input of size {size}",
+                    solution.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn safe_solutions_handle_invalid_input() {
+        // Failing and returning garbage are both fine, as long as there is no UB, which running
+        // this under `cargo miri test` would catch.
+        for solution in PART_1.iter().chain(PART_2) {
+            if solution.name.ends_with("-unsafe") {
+                continue;
+            }
+            for input in ["x", "(x)", "(()\n", "\u{0}", ")x"] {
+                let _ = (solution.solve)(input);
+            }
+        }
+    }
 }